@@ -4,11 +4,12 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use clap::{Parser, command};
+use clap::Parser;
 use color_eyre::eyre::{Context, eyre};
 use module::{Module, ModuleError};
 use quote::quote;
 use rspirv::binary::ParseState;
+use rspirv_bindgen::ShaderError;
 use tracing::{error, info};
 use write::write_formatted;
 
@@ -19,6 +20,9 @@ mod write;
 #[command(version, about, long_about = None)]
 pub struct Cli {
     /// The path to a SPIR-V file or directory containing SPIR-V files.
+    ///
+    /// The shaders of a directory are linked with each other, so the next stages of every entry
+    /// point include the stages of all other shaders in the directory, related or not.
     source: PathBuf,
 
     // TODO specify instance index
@@ -54,23 +58,31 @@ impl Cli {
                             return Err(error).with_context(|| format!("Path: {:?}", entry.path()));
                         }
 
-                        ModuleError::ParseSpirv(state) => match state {
-                            ParseState::HeaderIncorrect => {
-                                info!("Skipping invalid SPIR-V file: {:#?}", entry.file_name());
-                                continue;
-                            }
-
-                            state => {
-                                return Err(state)
-                                    .with_context(|| format!("Path: {:?}", entry.path()));
-                            }
-                        },
+                        ModuleError::Shader(ShaderError::Parse(ParseState::HeaderIncorrect)) => {
+                            info!("Skipping invalid SPIR-V file: {:#?}", entry.file_name());
+                            continue;
+                        }
+
+                        // One unusable module must not stop the others from being generated.
+                        ModuleError::Shader(error) => {
+                            error!("Skipping {:#?}: {}", entry.file_name(), error);
+                            continue;
+                        }
                     },
                 };
 
                 modules.push(module);
             }
 
+            // Each shader may be linked with the stages of the others.
+            let execution_models: Vec<_> = modules
+                .iter()
+                .flat_map(|module| module.spirv.execution_models())
+                .collect();
+            for module in &mut modules {
+                module.spirv.link_stages(&execution_models);
+            }
+
             modules
         } else {
             return Err(eyre!("Source must be a regular file or directory."));
@@ -224,3 +236,135 @@ impl Cli {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use rspirv::dr::{Builder, Operand};
+    use spirv::{Decoration, ExecutionModel, StorageClass};
+    use syn::{Expr, Item, ItemMod, visit::Visit};
+
+    use super::{Cli, module::write_test_module};
+
+    fn write_entry_point(file_name: &str, execution_model: ExecutionModel) -> PathBuf {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        builder.entry_point(execution_model, main, "main", []);
+
+        write_test_module("unrelated_modules", file_name, builder).unwrap()
+    }
+
+    fn entry_point_module(items: &[Item], name: &str) -> ItemMod {
+        items
+            .iter()
+            .find_map(|item| match item {
+                Item::Mod(module) if module.ident == name => Some(module.clone()),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    fn next_stage(module: &ItemMod) -> Expr {
+        module
+            .content
+            .iter()
+            .flat_map(|(_, items)| items)
+            .find_map(|item| match item {
+                Item::Const(constant) if constant.ident == "NEXT_STAGE" => {
+                    Some(*constant.expr.clone())
+                }
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    /// The last segment of every path in a module.
+    fn paths(module: &ItemMod) -> Vec<String> {
+        #[derive(Default)]
+        struct Paths(Vec<String>);
+
+        impl<'ast> Visit<'ast> for Paths {
+            fn visit_path(&mut self, path: &'ast syn::Path) {
+                syn::visit::visit_path(self, path);
+                if let Some(segment) = path.segments.last() {
+                    self.0.push(segment.ident.to_string());
+                }
+            }
+        }
+
+        let mut paths = Paths::default();
+        paths.visit_item_mod(module);
+        paths.0
+    }
+
+    #[test]
+    fn directory_modules_are_linked() {
+        write_entry_point("mesh.spv", ExecutionModel::MeshEXT);
+        let task = write_entry_point("task.spv", ExecutionModel::TaskEXT);
+
+        let cli = Cli {
+            source: task.parent().unwrap().to_path_buf(),
+            output: None,
+        };
+        let modules = cli.read_source().unwrap();
+        assert_eq!(modules.len(), 2);
+
+        let items_of = |name: &str| {
+            let module = modules.iter().find(|module| module.name == name).unwrap();
+            syn::parse2::<syn::File>(module.to_tokens(None))
+                .unwrap()
+                .items
+        };
+
+        // The unrelated task shader is linked as the stage before the mesh shader.
+        let task_items = items_of("task");
+        let task = entry_point_module(&task_items, "task_ext_main");
+        assert_eq!(
+            next_stage(&task),
+            syn::parse_quote! {ash::vk::ShaderStageFlags::MESH_EXT}
+        );
+
+        // The mesh shader's own module has no task shader, so it can still be bound alone.
+        let mesh_items = items_of("mesh");
+        let mesh = entry_point_module(&mesh_items, "mesh_ext_main");
+        assert!(paths(&mesh).contains(&"NO_TASK_SHADER".to_string()));
+    }
+
+    #[test]
+    fn invalid_modules_are_skipped() {
+        let mut valid = Builder::new();
+        let main = valid.id();
+        valid.entry_point(ExecutionModel::Fragment, main, "main", []);
+        let valid = write_test_module("invalid_modules", "valid.spv", valid).unwrap();
+
+        // A sampler and a storage buffer sharing a binding cannot be combined.
+        let mut conflicting = Builder::new();
+        let uint = conflicting.type_int(32, 0);
+        let block = conflicting.type_struct([uint]);
+        let sampler = conflicting.type_sampler();
+        for (pointee, storage_class) in [
+            (block, StorageClass::StorageBuffer),
+            (sampler, StorageClass::UniformConstant),
+        ] {
+            let pointer = conflicting.type_pointer(None, storage_class, pointee);
+            let variable = conflicting.variable(pointer, None, storage_class, None);
+            conflicting.decorate(
+                variable,
+                Decoration::DescriptorSet,
+                [Operand::LiteralBit32(0)],
+            );
+            conflicting.decorate(variable, Decoration::Binding, [Operand::LiteralBit32(0)]);
+        }
+        write_test_module("invalid_modules", "conflicting.spv", conflicting).unwrap();
+
+        let cli = Cli {
+            source: valid.parent().unwrap().to_path_buf(),
+            output: None,
+        };
+        let modules = cli.read_source().unwrap();
+
+        let names: Vec<_> = modules.iter().map(|module| module.name.as_str()).collect();
+        assert_eq!(names, ["valid"]);
+    }
+}
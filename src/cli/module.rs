@@ -5,16 +5,16 @@ use std::{
 };
 
 use convert_case::{Case, Casing};
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, TokenStream};
 use quote::{ToTokens, format_ident, quote};
 use regex::Regex;
-use rspirv::binary::ParseState;
-use rspirv_bindgen::Shader;
+use rspirv_bindgen::{Shader, ShaderError};
 use thiserror::Error;
 
 pub struct Module {
     pub spirv: Shader,
     pub source: PathBuf,
+    pub source_bytes: Vec<u8>,
     pub name: String,
 }
 
@@ -41,6 +41,7 @@ impl Module {
         Ok(Self {
             spirv,
             source,
+            source_bytes,
             name,
         })
     }
@@ -67,23 +68,53 @@ impl Module {
     }
 
     fn bytes_tokens(&self, output_path: Option<&Path>) -> TokenStream {
-        match output_path.as_ref() {
+        // Without an output path to include the file relative to, the bytes are embedded directly.
+        let data = match output_path.as_ref() {
             Some(output_path) => {
                 let path = pathdiff::diff_paths(&self.source, output_path).unwrap();
                 let path_str = path.to_string_lossy().replace("\\", "/");
 
-                quote! {
-                    pub const BYTES: &[u8] = {
-                        #[repr(C, align(4))]
-                        struct Aligned<T: ?Sized>(T);
+                quote! {*include_bytes!(#path_str)}
+            }
+            None => {
+                let bytes = Literal::byte_string(&self.source_bytes);
+
+                quote! {*#bytes}
+            }
+        };
+
+        let bytes = quote! {
+            pub const BYTES: &[u8] = {
+                #[repr(C, align(4))]
+                struct Aligned<T: ?Sized>(T);
 
-                        const ALIGNED_DATA: &Aligned<[u8]> = &Aligned(*include_bytes!(#path_str));
+                const ALIGNED_DATA: &Aligned<[u8]> = &Aligned(#data);
+
+                &ALIGNED_DATA.0
+            };
+
+            // SAFETY: The bytes are aligned to 4 and a SPIR-V module is a whole number of words.
+            pub const WORDS: &[u32] =
+                unsafe { core::slice::from_raw_parts(BYTES.as_ptr().cast::<u32>(), BYTES.len() / 4) };
+        };
+
+        // OpenCL modules are not created through Vulkan.
+        if self.spirv.is_kernel {
+            return bytes;
+        }
 
-                        &ALIGNED_DATA.0
-                    };
-                }
+        quote! {
+            #bytes
+
+            pub fn shader_module_create_info() -> ash::vk::ShaderModuleCreateInfo<'static> {
+                ash::vk::ShaderModuleCreateInfo::default().code(WORDS)
+            }
+
+            pub unsafe fn create_shader_module(
+                device: &ash::Device,
+            ) -> ash::prelude::VkResult<ash::vk::ShaderModule> {
+                unsafe { device.create_shader_module(&shader_module_create_info(), None) }
             }
-            None => TokenStream::new(),
         }
     }
 }
@@ -94,5 +125,96 @@ pub enum ModuleError {
     Io(#[from] io::Error),
 
     #[error(transparent)]
-    ParseSpirv(#[from] ParseState),
+    Shader(#[from] ShaderError),
+}
+
+/// Writes a module assembled by the builder to a fresh directory for the test.
+#[cfg(test)]
+pub fn write_test_module(
+    test: &str,
+    file_name: &str,
+    builder: rspirv::dr::Builder,
+) -> io::Result<PathBuf> {
+    use rspirv::binary::Assemble;
+
+    let directory = std::env::temp_dir().join(format!("rspirv-bindgen-{test}"));
+    fs::create_dir_all(&directory)?;
+
+    let bytes: Vec<u8> = builder
+        .module()
+        .assemble()
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect();
+
+    let path = directory.join(file_name);
+    fs::write(&path, bytes)?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use rspirv::dr::Builder;
+    use spirv::{AddressingModel, ExecutionModel, MemoryModel};
+    use syn::{Item, ItemFn};
+
+    use super::{Module, write_test_module};
+
+    fn module(test: &str, execution_model: ExecutionModel, memory_model: MemoryModel) -> Module {
+        let mut builder = Builder::new();
+        builder.memory_model(AddressingModel::Logical, memory_model);
+        let main = builder.id();
+        builder.entry_point(execution_model, main, "main", []);
+
+        Module::new(write_test_module(test, "2-shader.spv", builder).unwrap()).unwrap()
+    }
+
+    fn items(module: &Module) -> Vec<Item> {
+        syn::parse2::<syn::File>(module.to_tokens(None))
+            .unwrap()
+            .items
+    }
+
+    fn find_fn(items: &[Item], name: &str) -> Option<ItemFn> {
+        items.iter().find_map(|item| match item {
+            Item::Fn(function) if function.sig.ident == name => Some(function.clone()),
+            _ => None,
+        })
+    }
+
+    fn has_const(items: &[Item], name: &str) -> bool {
+        items
+            .iter()
+            .any(|item| matches!(item, Item::Const(constant) if constant.ident == name))
+    }
+
+    #[test]
+    fn shader_module_helpers() {
+        let module = module("module", ExecutionModel::Vertex, MemoryModel::GLSL450);
+
+        // Leading digits and separators are replaced so the name is a valid identifier.
+        assert_eq!(module.name, "shader");
+
+        let items = items(&module);
+        assert!(has_const(&items, "BYTES"));
+        assert!(has_const(&items, "WORDS"));
+
+        let create_info = find_fn(&items, "shader_module_create_info").unwrap();
+        assert_eq!(create_info.sig.inputs.len(), 0);
+
+        let create = find_fn(&items, "create_shader_module").unwrap();
+        assert!(create.sig.unsafety.is_some());
+        assert_eq!(create.sig.inputs.len(), 1);
+    }
+
+    #[test]
+    fn kernel_has_no_shader_module() {
+        let module = module("kernel_module", ExecutionModel::Kernel, MemoryModel::OpenCL);
+
+        let items = items(&module);
+        assert!(has_const(&items, "WORDS"));
+        assert!(find_fn(&items, "shader_module_create_info").is_none());
+        assert!(find_fn(&items, "create_shader_module").is_none());
+    }
 }
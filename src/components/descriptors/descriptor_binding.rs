@@ -1,19 +1,32 @@
+use convert_case::{Case, Casing};
 use itertools::Itertools;
-use quote::{ToTokens, quote};
+use proc_macro2::TokenStream;
+use quote::{ToTokens, format_ident, quote};
 use rspirv::dr::{Instruction, Module};
-use spirv::{Decoration, ExecutionModel, Op};
+use spirv::{Decoration, Dim, ExecutionModel, ImageFormat, Op};
+use tracing::warn;
 
 use crate::{
-    types::{DescriptorType, FromInstruction, TypeSyntax},
-    utilities::execution_model_to_tokens,
+    types::{DescriptorType, FromInstruction, Image, Type, TypeSyntax, VulkanFormatTokens},
+    utilities::{execution_model_to_tokens, find_decoration, find_name_for_id, has_decoration},
 };
 
+use super::{DYNAMIC_BUFFER_SUFFIX, IMMUTABLE_SAMPLER_SUFFIX, INLINE_UNIFORM_BLOCK_SUFFIX};
+
 #[derive(Debug)]
 pub struct DescriptorBinding {
+    pub name: String,
     pub set: u32,
     pub binding: u32,
     pub binding_type: DescriptorType,
+    /// The number of descriptors, or bytes for an inline uniform block.
+    pub descriptor_count: u32,
     pub stages: Vec<ExecutionModel>,
+    pub immutable_samplers: bool,
+    pub input_attachment_index: Option<u32>,
+    pub image: Option<Image>,
+    pub non_readable: bool,
+    pub non_writable: bool,
 }
 
 impl FromInstruction for DescriptorBinding {
@@ -75,33 +88,90 @@ impl FromInstruction for DescriptorBinding {
             .unique()
             .collect();
 
-        let binding_type = spirv.types_global_values.iter().find_map(|instruction| {
-            if instruction.result_id? != type_id {
-                return None;
+        let variable = spirv
+            .types_global_values
+            .iter()
+            .find(|instruction| instruction.result_id == Some(type_id))?;
+        let mut binding_type = DescriptorType::from_instruction(variable, spirv)?;
+
+        // Unnamed variables, such as anonymous blocks, are named after their binding.
+        let name = find_name_for_id(type_id, spirv)
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("binding_{}", binding));
+
+        if name.ends_with(DYNAMIC_BUFFER_SUFFIX) {
+            binding_type = binding_type.to_dynamic().unwrap_or(binding_type);
+        }
+
+        let mut descriptor_count = 1;
+
+        // Inline uniform blocks count the bytes of their block rather than descriptors.
+        if name.ends_with(INLINE_UNIFORM_BLOCK_SUFFIX) {
+            match (binding_type, Type::from_instruction(variable, spirv)) {
+                (DescriptorType::UniformBuffer, Some(Type::Struct(structure))) => {
+                    binding_type = DescriptorType::InlineUniformBlock;
+                    descriptor_count = structure.layout.size() as u32;
+                }
+                _ => warn!(
+                    "Binding {name} is not a uniform block, so it is bound as a {binding_type:?} instead of an inline uniform block."
+                ),
             }
-            DescriptorType::from_instruction(instruction, spirv)
-        })?;
+        }
+
+        let immutable_samplers = name.ends_with(IMMUTABLE_SAMPLER_SUFFIX)
+            && matches!(
+                binding_type,
+                DescriptorType::Sampler | DescriptorType::CombinedImageSampler
+            );
+
+        let input_attachment_index = matches!(binding_type, DescriptorType::InputAttachment)
+            .then(|| find_decoration(type_id, Decoration::InputAttachmentIndex, spirv))
+            .flatten()
+            .map(|operands| operands[0].unwrap_literal_bit32());
+
+        let image = Image::from_instruction(variable, spirv);
+
+        let non_readable = has_decoration(type_id, Decoration::NonReadable, spirv);
+        let non_writable = has_decoration(type_id, Decoration::NonWritable, spirv);
 
         Some(Self {
+            name,
             set,
             binding,
             binding_type,
+            descriptor_count,
             stages,
+            immutable_samplers,
+            input_attachment_index,
+            image,
+            non_readable,
+            non_writable,
         })
     }
 }
 
 impl ToTokens for DescriptorBinding {
-    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
         let binding = self.binding;
         let binding_type = self.binding_type.to_type_syntax();
-        let stages: Vec<_> = self.stages.iter().map(execution_model_to_tokens).collect();
+        let descriptor_count = self.descriptor_count;
+        let stages: Vec<_> = self
+            .stages
+            .iter()
+            .filter_map(execution_model_to_tokens)
+            .collect();
+
+        let immutable_samplers = self.immutable_samplers.then(|| {
+            let samplers = self.samplers_ident();
+            quote! {.immutable_samplers(#samplers)}
+        });
 
         let new_tokens = quote! {
             ash::vk::DescriptorSetLayoutBinding::default()
                 .binding(#binding)
                 .descriptor_type(#binding_type)
-                .descriptor_count(1)
+                .descriptor_count(#descriptor_count)
+                #immutable_samplers
                 .stage_flags(
                     #( #stages )|*
                 )
@@ -110,3 +180,298 @@ impl ToTokens for DescriptorBinding {
         tokens.extend(new_tokens);
     }
 }
+
+impl DescriptorBinding {
+    pub fn field_ident(&self) -> syn::Ident {
+        format_ident!("{}", self.name.to_case(Case::Snake))
+    }
+
+    /// A module level constant describing this binding.
+    pub fn constant_ident(&self, constant: &str) -> syn::Ident {
+        format_ident!(
+            "SET_{}_{}_{}",
+            self.set,
+            self.name.to_case(Case::Constant),
+            constant
+        )
+    }
+
+    /// Constants describing the image views this binding expects.
+    pub fn image_tokens(&self) -> Option<TokenStream> {
+        let image = self.image.as_ref()?;
+
+        // Texel buffers are bound as buffer views, which only have a format.
+        let view = (image.dim != Dim::DimBuffer).then(|| {
+            let view_type = image.view_type_tokens().map(|view_type| {
+                let ident = self.constant_ident("VIEW_TYPE");
+                quote! {pub const #ident: ash::vk::ImageViewType = #view_type;}
+            });
+
+            let multisampled_ident = self.constant_ident("MULTISAMPLED");
+            let multisampled = image.multisampled;
+            let depth_ident = self.constant_ident("DEPTH");
+            let depth = image.depth;
+
+            quote! {
+                #view_type
+                pub const #multisampled_ident: bool = #multisampled;
+                pub const #depth_ident: bool = #depth;
+            }
+        });
+
+        let format = self.required_format_tokens(image);
+
+        Some(quote! {
+            #view
+            #format
+        })
+    }
+
+    /// The format storage images and texel buffers are accessed with, and a check for the
+    /// formats a view may use. Without a declared format the device must support accessing
+    /// storage images without a format instead.
+    fn required_format_tokens(&self, image: &Image) -> Option<TokenStream> {
+        if !matches!(
+            self.binding_type,
+            DescriptorType::StorageImage | DescriptorType::StorageTexelBuffer
+        ) {
+            return None;
+        }
+
+        let ident = self.constant_ident("REQUIRED_FORMAT");
+        let required_format = match image.format {
+            ImageFormat::Unknown => quote! {None},
+            _ => {
+                let format = image.to_format_tokens();
+                quote! {Some(#format)}
+            }
+        };
+
+        let check = format_ident!("set_{}_{}_supports_format", self.set, self.field_ident());
+        let mut feature_checks = Vec::new();
+        if !self.non_readable {
+            feature_checks
+                .push(quote! {features.shader_storage_image_read_without_format == ash::vk::TRUE});
+        }
+        if !self.non_writable {
+            feature_checks
+                .push(quote! {features.shader_storage_image_write_without_format == ash::vk::TRUE});
+        }
+        let feature_checks = if feature_checks.is_empty() {
+            quote! {{ let _ = features; true }}
+        } else {
+            quote! {#( #feature_checks )&&*}
+        };
+
+        Some(quote! {
+            pub const #ident: Option<ash::vk::Format> = #required_format;
+
+            pub fn #check(
+                format: ash::vk::Format,
+                features: &ash::vk::PhysicalDeviceFeatures,
+            ) -> bool {
+                match #ident {
+                    Some(required_format) => format == required_format,
+                    None => #feature_checks,
+                }
+            }
+        })
+    }
+
+    /// The `set_layouts` argument holding this binding's immutable samplers.
+    pub fn samplers_ident(&self) -> syn::Ident {
+        format_ident!("set_{}_{}_samplers", self.set, self.field_ident())
+    }
+
+    /// Whether the binding holds nothing but immutable samplers, which are never written.
+    fn is_immutable_sampler(&self) -> bool {
+        self.immutable_samplers && matches!(self.binding_type, DescriptorType::Sampler)
+    }
+
+    /// The type a writer holds to update this binding, `None` if it is not written with a
+    /// `WriteDescriptorSet` alone.
+    pub fn writer_field_type(&self) -> Option<TokenStream> {
+        if self.is_immutable_sampler() {
+            return None;
+        }
+
+        let field_type = match self.binding_type {
+            DescriptorType::UniformBuffer
+            | DescriptorType::StorageBuffer
+            | DescriptorType::UniformBufferDynamic
+            | DescriptorType::StorageBufferDynamic => {
+                quote! {ash::vk::DescriptorBufferInfo}
+            }
+
+            DescriptorType::Sampler
+            | DescriptorType::SampledImage
+            | DescriptorType::StorageImage
+            | DescriptorType::CombinedImageSampler
+            | DescriptorType::InputAttachment => quote! {ash::vk::DescriptorImageInfo},
+
+            DescriptorType::UniformTexelBuffer | DescriptorType::StorageTexelBuffer => {
+                quote! {ash::vk::BufferView}
+            }
+
+            DescriptorType::AccelerationStructure => {
+                quote! {ash::vk::WriteDescriptorSetAccelerationStructureKHR<'a>}
+            }
+
+            DescriptorType::InlineUniformBlock => {
+                quote! {ash::vk::WriteDescriptorSetInlineUniformBlock<'a>}
+            }
+        };
+
+        Some(field_type)
+    }
+
+    /// The type of this binding's data in a descriptor update template.
+    pub fn template_field_type(&self) -> Option<TokenStream> {
+        match self.binding_type {
+            DescriptorType::AccelerationStructure => {
+                Some(quote! {ash::vk::AccelerationStructureKHR})
+            }
+            DescriptorType::InlineUniformBlock => {
+                let size = self.descriptor_count as usize;
+                Some(quote! {[u8; #size]})
+            }
+            _ => self.writer_field_type(),
+        }
+    }
+
+    /// The argument type, the `DescriptorDataEXT` built from an argument named `data` and the
+    /// `PhysicalDeviceDescriptorBufferPropertiesEXT` size field of this binding's descriptor.
+    pub fn descriptor_buffer_tokens(&self) -> Option<(TokenStream, TokenStream, TokenStream)> {
+        if self.is_immutable_sampler() {
+            return None;
+        }
+
+        let image = quote! {&ash::vk::DescriptorImageInfo};
+        let address = quote! {&ash::vk::DescriptorAddressInfoEXT<'_>};
+
+        let (argument_type, data_field, size_field) = match self.binding_type {
+            DescriptorType::Sampler => (
+                quote! {&ash::vk::Sampler},
+                quote! {p_sampler},
+                quote! {sampler_descriptor_size},
+            ),
+            DescriptorType::CombinedImageSampler => (
+                image,
+                quote! {p_combined_image_sampler},
+                quote! {combined_image_sampler_descriptor_size},
+            ),
+            DescriptorType::SampledImage => (
+                image,
+                quote! {p_sampled_image},
+                quote! {sampled_image_descriptor_size},
+            ),
+            DescriptorType::StorageImage => (
+                image,
+                quote! {p_storage_image},
+                quote! {storage_image_descriptor_size},
+            ),
+            DescriptorType::InputAttachment => (
+                image,
+                quote! {p_input_attachment_image},
+                quote! {input_attachment_descriptor_size},
+            ),
+            DescriptorType::UniformTexelBuffer => (
+                address,
+                quote! {p_uniform_texel_buffer},
+                quote! {uniform_texel_buffer_descriptor_size},
+            ),
+            DescriptorType::StorageTexelBuffer => (
+                address,
+                quote! {p_storage_texel_buffer},
+                quote! {storage_texel_buffer_descriptor_size},
+            ),
+            DescriptorType::UniformBuffer => (
+                address,
+                quote! {p_uniform_buffer},
+                quote! {uniform_buffer_descriptor_size},
+            ),
+            DescriptorType::StorageBuffer => (
+                address,
+                quote! {p_storage_buffer},
+                quote! {storage_buffer_descriptor_size},
+            ),
+            DescriptorType::AccelerationStructure => (
+                quote! {ash::vk::DeviceAddress},
+                quote! {acceleration_structure},
+                quote! {acceleration_structure_descriptor_size},
+            ),
+
+            // Inline uniform blocks are copied into the buffer, see `write_<binding>` of the
+            // generated descriptor buffer layout.
+            DescriptorType::InlineUniformBlock => return None,
+
+            // Dynamic descriptors cannot be used with descriptor buffers.
+            DescriptorType::UniformBufferDynamic | DescriptorType::StorageBufferDynamic => {
+                return None;
+            }
+        };
+
+        let data = quote! {ash::vk::DescriptorDataEXT { #data_field: data }};
+
+        Some((argument_type, data, size_field))
+    }
+
+    pub fn write_tokens(&self) -> Option<TokenStream> {
+        if self.is_immutable_sampler() {
+            return None;
+        }
+
+        let field = self.field_ident();
+        let binding = self.binding;
+        let binding_type = self.binding_type.to_type_syntax();
+
+        let write = quote! {
+            ash::vk::WriteDescriptorSet::default()
+                .dst_set(set)
+                .dst_binding(#binding)
+                .descriptor_type(#binding_type)
+        };
+
+        let write = match self.binding_type {
+            DescriptorType::UniformBuffer
+            | DescriptorType::StorageBuffer
+            | DescriptorType::UniformBufferDynamic
+            | DescriptorType::StorageBufferDynamic => {
+                quote! {#write.buffer_info(core::slice::from_ref(&self.#field))}
+            }
+
+            DescriptorType::Sampler
+            | DescriptorType::SampledImage
+            | DescriptorType::StorageImage
+            | DescriptorType::CombinedImageSampler
+            | DescriptorType::InputAttachment => {
+                quote! {#write.image_info(core::slice::from_ref(&self.#field))}
+            }
+
+            DescriptorType::UniformTexelBuffer | DescriptorType::StorageTexelBuffer => {
+                quote! {#write.texel_buffer_view(core::slice::from_ref(&self.#field))}
+            }
+
+            // The acceleration structures are chained from the writer, which outlives the write.
+            DescriptorType::AccelerationStructure => quote! {
+                {
+                    let mut write = #write
+                        .descriptor_count(self.#field.acceleration_structure_count);
+                    write.p_next = core::ptr::from_ref(&self.#field).cast();
+                    write
+                }
+            },
+
+            // The block's bytes are written from the first, the count being its size.
+            DescriptorType::InlineUniformBlock => quote! {
+                {
+                    let mut write = #write.descriptor_count(self.#field.data_size);
+                    write.p_next = core::ptr::from_ref(&self.#field).cast();
+                    write
+                }
+            },
+        };
+
+        Some(write)
+    }
+}
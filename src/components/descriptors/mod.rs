@@ -2,18 +2,81 @@ use std::collections::HashMap;
 
 use descriptor_binding::DescriptorBinding;
 use itertools::Itertools;
+use proc_macro2::TokenStream;
 use quote::{ToTokens, format_ident, quote};
 use rspirv::dr::Module;
+use thiserror::Error;
 
-use crate::types::FromInstruction;
+use crate::types::{DescriptorType, FromInstruction, TypeSyntax};
 
 use super::FromSpirv;
 
 mod descriptor_binding;
 
+/// Bindings named with this suffix mark their set as a push descriptor set.
+const PUSH_DESCRIPTOR_SUFFIX: &str = "_push";
+
+/// Uniform and storage buffers named with this suffix are bound with a dynamic offset.
+const DYNAMIC_BUFFER_SUFFIX: &str = "_dynamic";
+
+/// Sampler and combined image sampler bindings named with this suffix use immutable samplers.
+const IMMUTABLE_SAMPLER_SUFFIX: &str = "_immutable";
+
+/// Uniform buffers named with this suffix are inline uniform blocks.
+const INLINE_UNIFORM_BLOCK_SUFFIX: &str = "_inline";
+
+/// The minimum `maxPushDescriptors` from the required limits table of the Vulkan specification.
+/// Devices without `VK_KHR_push_descriptor` have no push descriptors at all, and larger sets
+/// may still work on devices reporting a higher limit.
+const MAX_PUSH_DESCRIPTORS: u32 = 32;
+
+/// A descriptor set layout that cannot be used as reflected.
+#[derive(Debug, Error)]
+pub enum DescriptorSetError {
+    /// Only one push descriptor set may be used in a pipeline layout.
+    #[error("Sets {0:?} are all push descriptor sets, a pipeline layout can only contain one.")]
+    MultiplePushDescriptorSets(Vec<u32>),
+
+    /// A push descriptor set has more descriptors than the minimum `maxPushDescriptors`.
+    #[error(
+        "Push descriptor set {set} has {count} descriptors, more than the minimum maxPushDescriptors of {MAX_PUSH_DESCRIPTORS} required by the Vulkan specification."
+    )]
+    TooManyPushDescriptors {
+        /// The push descriptor set.
+        set: u32,
+        /// The number of descriptors in the set.
+        count: u32,
+    },
+
+    /// Variables of incompatible descriptor types share a binding.
+    #[error(
+        "Binding {binding} of set {set} is declared with conflicting descriptor types by {names:?}."
+    )]
+    ConflictingBindingTypes {
+        /// The set of the binding.
+        set: u32,
+        /// The shared binding.
+        binding: u32,
+        /// The names of the variables sharing the binding.
+        names: Vec<String>,
+    },
+
+    /// Dynamic buffers cannot be used in a push descriptor set.
+    #[error("Binding {binding} of push descriptor set {set} is a dynamic buffer.")]
+    DynamicBufferInPushDescriptorSet {
+        /// The push descriptor set.
+        set: u32,
+        /// The dynamic buffer binding.
+        binding: u32,
+    },
+}
+
 #[derive(Debug)]
 pub struct DescriptorSets {
     pub sets: HashMap<u32, Vec<DescriptorBinding>>,
+    pub push_descriptor_sets: Vec<u32>,
+    /// The set, binding and variable names of bindings declared with incompatible types.
+    pub conflicting_bindings: Vec<(u32, u32, Vec<String>)>,
 }
 
 impl FromSpirv for DescriptorSets {
@@ -29,7 +92,8 @@ impl FromSpirv for DescriptorSets {
             return None;
         }
 
-        // TODO I don't like this.
+        let mut conflicting_bindings = Vec::new();
+
         // Merge descriptors with the same set and binding.
         sets.iter_mut().for_each(|(set, descriptors)| {
             let mut merged_descriptors: Vec<DescriptorBinding> = Vec::new();
@@ -42,24 +106,76 @@ impl FromSpirv for DescriptorSets {
                     continue;
                 }
 
-                let stages: Vec<_> = descriptors
+                let aliases: Vec<_> = descriptors
                     .iter()
-                    .filter_map(|other| {
-                        if other.binding == descriptor.binding {
-                            Some(other.stages.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .flatten()
+                    .filter(|other| other.binding == descriptor.binding)
+                    .collect();
+
+                let stages: Vec<_> = aliases
+                    .iter()
+                    .flat_map(|other| other.stages.clone())
+                    .unique()
+                    .collect();
+
+                let binding_types: Vec<_> = aliases
+                    .iter()
+                    .map(|other| other.binding_type)
                     .unique()
+                    .sorted()
                     .collect();
 
+                let binding_type = match binding_types.as_slice() {
+                    [binding_type] => *binding_type,
+
+                    // A separate sampler and image sharing a binding, as emitted from HLSL and Slang.
+                    [DescriptorType::Sampler, DescriptorType::SampledImage]
+                    | [
+                        DescriptorType::Sampler,
+                        DescriptorType::SampledImage,
+                        DescriptorType::CombinedImageSampler,
+                    ]
+                    | [
+                        DescriptorType::Sampler,
+                        DescriptorType::CombinedImageSampler,
+                    ]
+                    | [
+                        DescriptorType::SampledImage,
+                        DescriptorType::CombinedImageSampler,
+                    ] => DescriptorType::CombinedImageSampler,
+
+                    _ => {
+                        conflicting_bindings.push((
+                            *set,
+                            descriptor.binding,
+                            aliases.iter().map(|other| other.name.clone()).collect(),
+                        ));
+                        descriptor.binding_type
+                    }
+                };
+
+                // Combined bindings are named after their image rather than their sampler.
+                let name = aliases
+                    .iter()
+                    .find(|other| other.binding_type != DescriptorType::Sampler)
+                    .unwrap_or(&descriptor)
+                    .name
+                    .clone();
+
+                // The sampler of a combined binding may be the one declared immutable.
+                let immutable_samplers = aliases.iter().any(|other| other.immutable_samplers);
+
                 let descriptor = DescriptorBinding {
+                    name,
                     set: *set,
                     binding: descriptor.binding,
-                    binding_type: descriptor.binding_type,
+                    binding_type,
+                    descriptor_count: descriptor.descriptor_count,
                     stages,
+                    immutable_samplers,
+                    input_attachment_index: descriptor.input_attachment_index,
+                    image: aliases.iter().find_map(|other| other.image.clone()),
+                    non_readable: aliases.iter().all(|other| other.non_readable),
+                    non_writable: aliases.iter().all(|other| other.non_writable),
                 };
 
                 merged_descriptors.push(descriptor);
@@ -68,12 +184,426 @@ impl FromSpirv for DescriptorSets {
             *descriptors = merged_descriptors
         });
 
-        Some(Self { sets })
+        conflicting_bindings.sort();
+
+        let push_descriptor_sets = sets
+            .iter()
+            .filter(|(_, bindings)| {
+                bindings
+                    .iter()
+                    .any(|binding| binding.name.ends_with(PUSH_DESCRIPTOR_SUFFIX))
+            })
+            .map(|(set, _)| *set)
+            .sorted()
+            .collect();
+
+        Some(Self {
+            sets,
+            push_descriptor_sets,
+            conflicting_bindings,
+        })
+    }
+}
+
+/// The pool sizes for the bindings, `count` being the number of times each binding is allocated.
+fn pool_size_tokens(bindings: &[&DescriptorBinding], count: TokenStream) -> Vec<TokenStream> {
+    bindings
+        .iter()
+        .into_grouping_map_by(|binding| binding.binding_type)
+        .fold(0, |count, _, binding| count + binding.descriptor_count)
+        .into_iter()
+        .sorted_by_key(|(binding_type, _)| *binding_type)
+        .map(|(binding_type, descriptor_count)| {
+            let binding_type = binding_type.to_type_syntax();
+            quote! {
+                ash::vk::DescriptorPoolSize::default()
+                    .ty(#binding_type)
+                    .descriptor_count(#descriptor_count * #count)
+            }
+        })
+        .collect()
+}
+
+impl DescriptorSets {
+    pub fn validate(&self) -> Result<(), DescriptorSetError> {
+        if let Some((set, binding, names)) = self.conflicting_bindings.first() {
+            return Err(DescriptorSetError::ConflictingBindingTypes {
+                set: *set,
+                binding: *binding,
+                names: names.clone(),
+            });
+        }
+
+        if self.push_descriptor_sets.len() > 1 {
+            return Err(DescriptorSetError::MultiplePushDescriptorSets(
+                self.push_descriptor_sets.clone(),
+            ));
+        }
+
+        for &set in &self.push_descriptor_sets {
+            let bindings = self.set_bindings(set);
+
+            // Inline uniform blocks count their bytes, but are a single descriptor.
+            let count = bindings
+                .iter()
+                .map(|binding| match binding.binding_type {
+                    DescriptorType::InlineUniformBlock => 1,
+                    _ => binding.descriptor_count,
+                })
+                .sum();
+
+            if count > MAX_PUSH_DESCRIPTORS {
+                return Err(DescriptorSetError::TooManyPushDescriptors { set, count });
+            }
+
+            if let Some(binding) = bindings
+                .iter()
+                .find(|binding| binding.binding_type.is_dynamic())
+            {
+                return Err(DescriptorSetError::DynamicBufferInPushDescriptorSet {
+                    set,
+                    binding: binding.binding,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_push_descriptor_set(&self, set: u32) -> bool {
+        self.push_descriptor_sets.contains(&set)
+    }
+
+    /// The bindings of a set, ordered by binding number.
+    fn set_bindings(&self, set: u32) -> Vec<&DescriptorBinding> {
+        self.sets
+            .get(&set)
+            .map(|bindings| {
+                bindings
+                    .iter()
+                    .sorted_by_key(|binding| binding.binding)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn writer_tokens(&self, set: u32) -> Option<TokenStream> {
+        let bindings: Vec<_> = self
+            .set_bindings(set)
+            .into_iter()
+            .filter(|binding| binding.writer_field_type().is_some())
+            .collect();
+
+        if bindings.is_empty() {
+            return None;
+        }
+
+        let ident = format_ident!("Set{}Writer", set);
+        let fields = bindings.iter().map(|binding| binding.field_ident());
+        let field_types = bindings.iter().map(|binding| binding.writer_field_type());
+        let writes = bindings.iter().map(|binding| binding.write_tokens());
+
+        let push_descriptor = self.is_push_descriptor_set(set).then(|| {
+            quote! {
+                pub unsafe fn cmd_push_descriptor_set(
+                    &self,
+                    device: &ash::khr::push_descriptor::Device,
+                    command_buffer: ash::vk::CommandBuffer,
+                    bind_point: ash::vk::PipelineBindPoint,
+                    layout: ash::vk::PipelineLayout,
+                ) {
+                    let writes = self.writes(ash::vk::DescriptorSet::null());
+                    unsafe {
+                        device.cmd_push_descriptor_set(command_buffer, bind_point, layout, #set, &writes)
+                    }
+                }
+            }
+        });
+
+        // Acceleration structure and inline uniform block writes borrow their data.
+        let lifetime = bindings
+            .iter()
+            .any(|binding| {
+                matches!(
+                    binding.binding_type,
+                    DescriptorType::AccelerationStructure | DescriptorType::InlineUniformBlock
+                )
+            })
+            .then(|| quote! {<'a>});
+
+        Some(quote! {
+            #[derive(Clone, Copy, Default)]
+            pub struct #ident #lifetime {
+                #( pub #fields: #field_types ),*
+            }
+
+            impl #lifetime #ident #lifetime {
+                pub fn writes(&self, set: ash::vk::DescriptorSet) -> Vec<ash::vk::WriteDescriptorSet<'_>> {
+                    vec![
+                        #( #writes ),*
+                    ]
+                }
+
+                #push_descriptor
+            }
+        })
+    }
+
+    fn descriptor_buffer_tokens(&self, set: u32) -> Option<TokenStream> {
+        // Push descriptors are not stored in a descriptor buffer.
+        if self.is_push_descriptor_set(set) {
+            return None;
+        }
+
+        let bindings: Vec<_> = self
+            .set_bindings(set)
+            .into_iter()
+            .filter_map(|binding| Some((binding, binding.descriptor_buffer_tokens()?)))
+            .collect();
+
+        // Inline uniform blocks are copied into the buffer rather than fetched as descriptors.
+        let inline_bindings: Vec<_> = self
+            .set_bindings(set)
+            .into_iter()
+            .filter(|binding| matches!(binding.binding_type, DescriptorType::InlineUniformBlock))
+            .collect();
+
+        if bindings.is_empty() && inline_bindings.is_empty() {
+            return None;
+        }
+
+        let ident = format_ident!("Set{}DescriptorBufferLayout", set);
+
+        let inline_offset_fields: Vec<_> = inline_bindings
+            .iter()
+            .map(|binding| format_ident!("{}_offset", binding.field_ident()))
+            .collect();
+        let inline_binding_numbers = inline_bindings.iter().map(|binding| binding.binding);
+
+        let inline_write_methods =
+            inline_bindings
+                .iter()
+                .zip(&inline_offset_fields)
+                .map(|(binding, offset_field)| {
+                    let method = format_ident!("write_{}", binding.field_ident());
+                    let size = binding.descriptor_count as usize;
+
+                    quote! {
+                        pub fn #method(&self, set_data: &mut [u8], data: &[u8; #size]) {
+                            let offset = self.#offset_field as usize;
+                            set_data[offset..offset + #size].copy_from_slice(data);
+                        }
+                    }
+                });
+
+        let offset_fields: Vec<_> = bindings
+            .iter()
+            .map(|(binding, _)| format_ident!("{}_offset", binding.field_ident()))
+            .collect();
+        let size_fields: Vec<_> = bindings
+            .iter()
+            .map(|(binding, _)| format_ident!("{}_size", binding.field_ident()))
+            .collect();
+        let binding_numbers = bindings.iter().map(|(binding, _)| binding.binding);
+        let property_sizes = bindings.iter().map(|(_, (_, _, size_field))| size_field);
+
+        let write_methods = bindings.iter().zip(offset_fields.iter().zip(&size_fields)).map(
+            |((binding, (argument_type, data, _)), (offset_field, size_field))| {
+                let method = format_ident!("write_{}", binding.field_ident());
+                let binding_type = binding.binding_type.to_type_syntax();
+
+                quote! {
+                    pub unsafe fn #method(
+                        &self,
+                        device: &ash::ext::descriptor_buffer::Device,
+                        set_data: &mut [u8],
+                        data: #argument_type,
+                    ) {
+                        let offset = self.#offset_field as usize;
+                        let info = ash::vk::DescriptorGetInfoEXT::default()
+                            .ty(#binding_type)
+                            .data(#data);
+
+                        unsafe { device.get_descriptor(&info, &mut set_data[offset..offset + self.#size_field]) }
+                    }
+                }
+            },
+        );
+
+        Some(quote! {
+            #[derive(Clone, Copy, Debug, Default)]
+            pub struct #ident {
+                pub size: u64,
+                #( pub #offset_fields: u64, )*
+                #( pub #inline_offset_fields: u64, )*
+                #( pub #size_fields: usize, )*
+            }
+
+            impl #ident {
+                pub unsafe fn new(
+                    device: &ash::ext::descriptor_buffer::Device,
+                    layout: ash::vk::DescriptorSetLayout,
+                    properties: &ash::vk::PhysicalDeviceDescriptorBufferPropertiesEXT<'_>,
+                ) -> Self {
+                    unsafe {
+                        Self {
+                            size: device.get_descriptor_set_layout_size(layout),
+                            #( #offset_fields: device.get_descriptor_set_layout_binding_offset(layout, #binding_numbers), )*
+                            #( #inline_offset_fields: device.get_descriptor_set_layout_binding_offset(layout, #inline_binding_numbers), )*
+                            #( #size_fields: properties.#property_sizes, )*
+                        }
+                    }
+                }
+
+                #( #write_methods )*
+                #( #inline_write_methods )*
+            }
+        })
+    }
+
+    fn template_tokens(&self, set: u32) -> Option<TokenStream> {
+        let bindings: Vec<_> = self
+            .set_bindings(set)
+            .into_iter()
+            .filter(|binding| binding.template_field_type().is_some())
+            .collect();
+
+        if bindings.is_empty() {
+            return None;
+        }
+
+        let ident = format_ident!("Set{}TemplateData", set);
+        let fields: Vec<_> = bindings
+            .iter()
+            .map(|binding| binding.field_ident())
+            .collect();
+        let field_types: Vec<_> = bindings
+            .iter()
+            .map(|binding| binding.template_field_type())
+            .collect();
+        let entry_count = bindings.len();
+
+        // Inline uniform blocks are byte arrays, which only implement `Default` up to 32 bytes.
+        let defaults = bindings.iter().map(|binding| {
+            if matches!(binding.binding_type, DescriptorType::InlineUniformBlock) {
+                let size = binding.descriptor_count as usize;
+                quote! {[0; #size]}
+            } else {
+                quote! {Default::default()}
+            }
+        });
+
+        let entries =
+            bindings
+                .iter()
+                .zip(&fields)
+                .zip(&field_types)
+                .map(|((binding, field), field_type)| {
+                    let binding_number = binding.binding;
+                    let binding_type = binding.binding_type.to_type_syntax();
+                    let descriptor_count = binding.descriptor_count;
+
+                    quote! {
+                        ash::vk::DescriptorUpdateTemplateEntry {
+                            dst_binding: #binding_number,
+                            dst_array_element: 0,
+                            descriptor_count: #descriptor_count,
+                            descriptor_type: #binding_type,
+                            offset: core::mem::offset_of!(#ident, #field),
+                            stride: core::mem::size_of::<#field_type>(),
+                        }
+                    }
+                });
+
+        // Push descriptor sets are updated through the command buffer instead of a set.
+        let update_tokens = if self.is_push_descriptor_set(set) {
+            quote! {
+                pub fn update_template_create_info<'a>(
+                    bind_point: ash::vk::PipelineBindPoint,
+                    layout: ash::vk::PipelineLayout,
+                ) -> ash::vk::DescriptorUpdateTemplateCreateInfo<'a> {
+                    ash::vk::DescriptorUpdateTemplateCreateInfo::default()
+                        .descriptor_update_entries(&Self::UPDATE_TEMPLATE_ENTRIES)
+                        .template_type(ash::vk::DescriptorUpdateTemplateType::PUSH_DESCRIPTORS_KHR)
+                        .pipeline_bind_point(bind_point)
+                        .pipeline_layout(layout)
+                        .set(#set)
+                }
+
+                pub unsafe fn cmd_push_descriptor_set(
+                    &self,
+                    device: &ash::khr::push_descriptor::Device,
+                    command_buffer: ash::vk::CommandBuffer,
+                    template: ash::vk::DescriptorUpdateTemplate,
+                    layout: ash::vk::PipelineLayout,
+                ) {
+                    unsafe {
+                        device.cmd_push_descriptor_set_with_template(
+                            command_buffer,
+                            template,
+                            layout,
+                            #set,
+                            core::ptr::from_ref(self).cast(),
+                        )
+                    }
+                }
+            }
+        } else {
+            quote! {
+                    pub fn update_template_create_info<'a>(
+                        layout: ash::vk::DescriptorSetLayout,
+                    ) -> ash::vk::DescriptorUpdateTemplateCreateInfo<'a> {
+                        ash::vk::DescriptorUpdateTemplateCreateInfo::default()
+                            .descriptor_update_entries(&Self::UPDATE_TEMPLATE_ENTRIES)
+                            .template_type(ash::vk::DescriptorUpdateTemplateType::DESCRIPTOR_SET)
+                            .descriptor_set_layout(layout)
+                    }
+
+                    pub unsafe fn update(
+                        &self,
+                        device: &ash::Device,
+                        set: ash::vk::DescriptorSet,
+                        template: ash::vk::DescriptorUpdateTemplate,
+                    ) {
+                        unsafe {
+                            device.update_descriptor_set_with_template(
+                                set,
+                                template,
+                                core::ptr::from_ref(self).cast(),
+                            )
+                        }
+                    }
+            }
+        };
+
+        Some(quote! {
+            #[repr(C)]
+            #[derive(Clone, Copy)]
+            pub struct #ident {
+                #( pub #fields: #field_types ),*
+            }
+
+            impl Default for #ident {
+                fn default() -> Self {
+                    Self {
+                        #( #fields: #defaults ),*
+                    }
+                }
+            }
+
+            impl #ident {
+                pub const UPDATE_TEMPLATE_ENTRIES: [ash::vk::DescriptorUpdateTemplateEntry; #entry_count] = [
+                    #( #entries ),*
+                ];
+
+                #update_tokens
+            }
+        })
     }
 }
 
 impl ToTokens for DescriptorSets {
-    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
         let Some(set_count) = self.sets.keys().max() else {
             return;
         };
@@ -97,6 +627,13 @@ impl ToTokens for DescriptorSets {
                 None => quote! {[]},
             };
 
+            // Push descriptor sets cannot be combined with other flags, such as update after bind.
+            let flags = if self.is_push_descriptor_set(set as u32) {
+                quote! {ash::vk::DescriptorSetLayoutCreateFlags::PUSH_DESCRIPTOR_KHR}
+            } else {
+                quote! {flags}
+            };
+
             let cleanup: Vec<_> = (0..set)
                 .map(|set| {
                     let ident = &set_idents[set];
@@ -113,7 +650,7 @@ impl ToTokens for DescriptorSets {
 
                     let layout_info = ash::vk::DescriptorSetLayoutCreateInfo::default()
                         .bindings(&bindings)
-                        .flags(flags);
+                        .flags(#flags);
 
                     match unsafe { device.create_descriptor_set_layout(&layout_info, None) } {
                         Ok(set) => set,
@@ -126,10 +663,235 @@ impl ToTokens for DescriptorSets {
             }
         });
 
+        let set_pool_sizes = (0..set_count).filter_map(|set| {
+            if self.is_push_descriptor_set(set as u32) {
+                return None;
+            }
+
+            let ident = format_ident!("set_{}_pool_sizes", set);
+            let bindings: Vec<_> = self
+                .sets
+                .get(&(set as u32))
+                .map(|bindings| bindings.iter().collect())
+                .unwrap_or_default();
+            let sizes = pool_size_tokens(&bindings, quote! {set_count});
+            let size_count = sizes.len();
+
+            Some(quote! {
+                pub fn #ident(set_count: u32) -> [ash::vk::DescriptorPoolSize; #size_count] {
+                    [
+                        #( #sizes ),*
+                    ]
+                }
+            })
+        });
+
+        let pool_sizes = {
+            // Push descriptor sets are never allocated from a pool.
+            let bindings: Vec<_> = self
+                .sets
+                .iter()
+                .filter(|(set, _)| !self.is_push_descriptor_set(**set))
+                .flat_map(|(_, bindings)| bindings)
+                .collect();
+            let sizes = pool_size_tokens(&bindings, quote! {sets_per_layout});
+
+            quote! {
+                pub fn pool_sizes(sets_per_layout: u32) -> Vec<ash::vk::DescriptorPoolSize> {
+                    vec![
+                        #( #sizes ),*
+                    ]
+                }
+            }
+        };
+
+        // Dynamic offsets must be multiples of minUniformBufferOffsetAlignment or
+        // minStorageBufferOffsetAlignment, depending on the buffer type, and fit in a u32.
+        let dynamic_offset = self
+            .sets
+            .values()
+            .flatten()
+            .any(|binding| binding.binding_type.is_dynamic())
+            .then(|| {
+                quote! {
+                    pub const fn dynamic_offset(
+                        element_size: ash::vk::DeviceSize,
+                        index: u32,
+                        min_offset_alignment: ash::vk::DeviceSize,
+                    ) -> Option<u32> {
+                        match element_size
+                            .next_multiple_of(min_offset_alignment)
+                            .checked_mul(index as u64)
+                        {
+                            Some(offset) if offset <= u32::MAX as u64 => Some(offset as u32),
+                            _ => None,
+                        }
+                    }
+                }
+            });
+
+        // A sampler per descriptor, as the layout takes its descriptor count from the samplers.
+        let immutable_samplers = (0..set_count as u32).flat_map(|set| {
+            self.set_bindings(set)
+                .into_iter()
+                .filter(|binding| binding.immutable_samplers)
+                .map(|binding| {
+                    let ident = binding.samplers_ident();
+                    let count = binding.descriptor_count as usize;
+                    quote! {#ident: &[ash::vk::Sampler; #count]}
+                })
+        });
+
+        let inline_uniform_blocks = {
+            let bindings: Vec<_> = self
+                .sets
+                .iter()
+                .filter(|(set, _)| !self.is_push_descriptor_set(**set))
+                .flat_map(|(_, bindings)| bindings)
+                .filter(|binding| {
+                    matches!(binding.binding_type, DescriptorType::InlineUniformBlock)
+                })
+                .collect();
+            let max_size = self
+                .sets
+                .values()
+                .flatten()
+                .filter(|binding| {
+                    matches!(binding.binding_type, DescriptorType::InlineUniformBlock)
+                })
+                .map(|binding| binding.descriptor_count)
+                .max();
+
+            // Pools need the number of inline uniform block bindings they may allocate.
+            let pool_info = (!bindings.is_empty()).then(|| {
+                let binding_count = bindings.len() as u32;
+
+                quote! {
+                    pub fn inline_uniform_block_pool_info(
+                        sets_per_layout: u32,
+                    ) -> ash::vk::DescriptorPoolInlineUniformBlockCreateInfo<'static> {
+                        ash::vk::DescriptorPoolInlineUniformBlockCreateInfo::default()
+                            .max_inline_uniform_block_bindings(#binding_count * sets_per_layout)
+                    }
+                }
+            });
+
+            let limits = max_size.map(|max_size| {
+                quote! {
+                    pub fn within_inline_uniform_block_limits(
+                        properties: &ash::vk::PhysicalDeviceInlineUniformBlockProperties<'_>,
+                    ) -> bool {
+                        #max_size <= properties.max_inline_uniform_block_size
+                    }
+                }
+            });
+
+            quote! {
+                #pool_info
+                #limits
+            }
+        };
+
+        let input_attachments = {
+            let bindings: Vec<_> = (0..set_count as u32)
+                .flat_map(|set| self.set_bindings(set))
+                .filter_map(|binding| Some((binding, binding.input_attachment_index?)))
+                .collect();
+
+            let constants = bindings.iter().map(|(binding, index)| {
+                let ident = binding.constant_ident("INPUT_ATTACHMENT_INDEX");
+                quote! {pub const #ident: u32 = #index;}
+            });
+
+            // The attachments feeding each index are only known to the caller, so the mapping is
+            // taken as is and checked to cover every input attachment the shader reads.
+            let local_read = (!bindings.is_empty()).then(|| {
+                let indices = bindings.iter().map(|(_, index)| index).sorted().dedup();
+                let index_count = indices.clone().count();
+
+                let depth_index = match bindings.iter().find(|(binding, _)| {
+                    binding.image.as_ref().is_some_and(|image| image.depth)
+                }) {
+                    Some((_, index)) => quote! {Some(#index)},
+                    None => quote! {None},
+                };
+
+                quote! {
+                    pub const INPUT_ATTACHMENT_INDICES: [u32; #index_count] = [
+                        #( #indices ),*
+                    ];
+
+                    pub const DEPTH_INPUT_ATTACHMENT_INDEX: Option<u32> = #depth_index;
+
+                    pub fn rendering_input_attachment_index_info<'a>(
+                        color_attachment_input_indices: &'a [u32],
+                        depth_input_attachment_index: Option<&'a u32>,
+                        stencil_input_attachment_index: Option<&'a u32>,
+                    ) -> ash::vk::RenderingInputAttachmentIndexInfoKHR<'a> {
+                        debug_assert!(
+                            INPUT_ATTACHMENT_INDICES.iter().all(|index| {
+                                color_attachment_input_indices.contains(index)
+                                    || depth_input_attachment_index == Some(index)
+                                    || stencil_input_attachment_index == Some(index)
+                            }),
+                            "Every input attachment index read by the shader must be mapped to an attachment"
+                        );
+
+                        let mut info = ash::vk::RenderingInputAttachmentIndexInfoKHR::default()
+                            .color_attachment_input_indices(color_attachment_input_indices);
+                        if let Some(index) = depth_input_attachment_index {
+                            info = info.depth_input_attachment_index(index);
+                        }
+                        if let Some(index) = stencil_input_attachment_index {
+                            info = info.stencil_input_attachment_index(index);
+                        }
+                        info
+                    }
+                }
+            });
+
+            quote! {
+                #( #constants )*
+                #local_read
+            }
+        };
+
+        let images = (0..set_count as u32)
+            .flat_map(|set| self.set_bindings(set))
+            .filter_map(|binding| binding.image_tokens());
+
+        let set_writers = (0..set_count as u32).filter_map(|set| self.writer_tokens(set));
+        let set_templates = (0..set_count as u32).filter_map(|set| self.template_tokens(set));
+        let set_descriptor_buffers =
+            (0..set_count as u32).filter_map(|set| self.descriptor_buffer_tokens(set));
+
         let new_tokens = quote! {
+            #( #set_writers )*
+            #( #set_templates )*
+            #( #set_descriptor_buffers )*
+            #( #set_pool_sizes )*
+            #pool_sizes
+            #dynamic_offset
+            #inline_uniform_blocks
+            #input_attachments
+            #( #images )*
+
+            pub unsafe fn allocate_sets(
+                device: &ash::Device,
+                pool: ash::vk::DescriptorPool,
+                layouts: &[ash::vk::DescriptorSetLayout],
+            ) -> Result<Vec<ash::vk::DescriptorSet>, ash::vk::Result> {
+                let allocate_info = ash::vk::DescriptorSetAllocateInfo::default()
+                    .descriptor_pool(pool)
+                    .set_layouts(layouts);
+
+                unsafe { device.allocate_descriptor_sets(&allocate_info) }
+            }
+
             pub unsafe fn set_layouts(
                 device: &ash::Device,
                 flags: ash::vk::DescriptorSetLayoutCreateFlags,
+                #( #immutable_samplers, )*
             ) -> Result<Vec<ash::vk::DescriptorSetLayout>, ash::vk::Result> {
                 #( #set_tokens )*
 
@@ -142,3 +904,821 @@ impl ToTokens for DescriptorSets {
         tokens.extend(new_tokens);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use rspirv::dr::{Builder, Operand};
+    use spirv::{Decoration, Dim, ImageFormat, StorageClass};
+
+    use crate::{
+        components::FromSpirv,
+        types::DescriptorType,
+        utilities::{
+            accessed_fields, find_const, find_fn, find_struct, load_built_module, method_arguments,
+            parameter_names, parse_items, struct_fields,
+        },
+    };
+
+    use super::{DescriptorSetError, DescriptorSets};
+
+    fn variable(
+        builder: &mut Builder,
+        pointee: u32,
+        storage_class: StorageClass,
+        name: &str,
+        set: u32,
+        binding: u32,
+    ) -> u32 {
+        let pointer = builder.type_pointer(None, storage_class, pointee);
+        let variable = builder.variable(pointer, None, storage_class, None);
+        builder.name(variable, name);
+        builder.decorate(
+            variable,
+            Decoration::DescriptorSet,
+            [Operand::LiteralBit32(set)],
+        );
+        builder.decorate(
+            variable,
+            Decoration::Binding,
+            [Operand::LiteralBit32(binding)],
+        );
+        variable
+    }
+
+    fn sampled_image(builder: &mut Builder) -> u32 {
+        let float = builder.type_float(32);
+        builder.type_image(float, Dim::Dim2D, 0, 0, 0, 1, ImageFormat::Unknown, None)
+    }
+
+    /// A block of a single `vec4`, 16 bytes in size.
+    fn uniform_block(builder: &mut Builder) -> u32 {
+        let float = builder.type_float(32);
+        let vec4 = builder.type_vector(float, 4);
+        let block = builder.type_struct([vec4]);
+        builder.decorate(block, Decoration::Block, []);
+        builder.member_decorate(block, 0, Decoration::Offset, [Operand::LiteralBit32(0)]);
+        block
+    }
+
+    /// A uniform buffer and two sampled images in set 0, and a storage buffer in set 2.
+    fn material_sets() -> DescriptorSets {
+        let mut builder = Builder::new();
+        let block = uniform_block(&mut builder);
+        let image = sampled_image(&mut builder);
+        variable(&mut builder, block, StorageClass::Uniform, "material", 0, 0);
+        variable(
+            &mut builder,
+            image,
+            StorageClass::UniformConstant,
+            "albedo",
+            0,
+            1,
+        );
+        variable(
+            &mut builder,
+            image,
+            StorageClass::UniformConstant,
+            "normal",
+            0,
+            2,
+        );
+        variable(
+            &mut builder,
+            block,
+            StorageClass::StorageBuffer,
+            "lights",
+            2,
+            0,
+        );
+        let spirv = load_built_module(builder);
+
+        let descriptor_sets = DescriptorSets::from_spirv(&spirv).unwrap();
+        descriptor_sets.validate().unwrap();
+        descriptor_sets
+    }
+
+    #[test]
+    fn pool_sizes() {
+        let items = parse_items(material_sets().to_token_stream());
+
+        let set_0 = find_fn(&items, "set_0_pool_sizes").unwrap();
+        assert_eq!(
+            set_0.sig.output,
+            syn::parse_quote! {-> [ash::vk::DescriptorPoolSize; 2usize]}
+        );
+        assert_eq!(
+            method_arguments(&set_0, "ty"),
+            [
+                [syn::parse_quote! {ash::vk::DescriptorType::SAMPLED_IMAGE}],
+                [syn::parse_quote! {ash::vk::DescriptorType::UNIFORM_BUFFER}],
+            ]
+        );
+        assert_eq!(
+            method_arguments(&set_0, "descriptor_count"),
+            [
+                [syn::parse_quote! {2u32 * set_count}],
+                [syn::parse_quote! {1u32 * set_count}],
+            ]
+        );
+
+        // Sets without bindings have nothing to allocate.
+        let set_1 = find_fn(&items, "set_1_pool_sizes").unwrap();
+        assert_eq!(
+            set_1.sig.output,
+            syn::parse_quote! {-> [ash::vk::DescriptorPoolSize; 0usize]}
+        );
+
+        // The sizes of every set are summed by descriptor type.
+        let pool_sizes = find_fn(&items, "pool_sizes").unwrap();
+        assert_eq!(
+            method_arguments(&pool_sizes, "descriptor_count"),
+            [
+                [syn::parse_quote! {2u32 * sets_per_layout}],
+                [syn::parse_quote! {1u32 * sets_per_layout}],
+                [syn::parse_quote! {1u32 * sets_per_layout}],
+            ]
+        );
+
+        let allocate = find_fn(&items, "allocate_sets").unwrap();
+        assert_eq!(parameter_names(&allocate), ["device", "pool", "layouts"]);
+    }
+
+    #[test]
+    fn set_writers() {
+        let items = parse_items(material_sets().to_token_stream());
+
+        let writer = find_struct(&items, "Set0Writer").unwrap();
+        assert_eq!(
+            struct_fields(&writer),
+            [
+                (
+                    "material".to_string(),
+                    syn::parse_quote! {ash::vk::DescriptorBufferInfo}
+                ),
+                (
+                    "albedo".to_string(),
+                    syn::parse_quote! {ash::vk::DescriptorImageInfo}
+                ),
+                (
+                    "normal".to_string(),
+                    syn::parse_quote! {ash::vk::DescriptorImageInfo}
+                ),
+            ]
+        );
+        assert!(find_struct(&items, "Set1Writer").is_none());
+
+        let writes = find_fn(&items, "Set0Writer::writes").unwrap();
+        assert_eq!(parameter_names(&writes), ["self", "set"]);
+        assert_eq!(
+            method_arguments(&writes, "dst_binding"),
+            [
+                [syn::parse_quote! {0u32}],
+                [syn::parse_quote! {1u32}],
+                [syn::parse_quote! {2u32}],
+            ]
+        );
+        assert_eq!(method_arguments(&writes, "buffer_info").len(), 1);
+        assert_eq!(method_arguments(&writes, "image_info").len(), 2);
+
+        // Only push descriptor sets are pushed.
+        assert!(find_fn(&items, "Set0Writer::cmd_push_descriptor_set").is_none());
+    }
+
+    #[test]
+    fn update_templates() {
+        let items = parse_items(material_sets().to_token_stream());
+
+        let data = find_struct(&items, "Set0TemplateData").unwrap();
+        let fields: Vec<_> = struct_fields(&data)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(fields, ["material", "albedo", "normal"]);
+
+        let Some(syn::Expr::Array(entries)) =
+            find_const(&items, "Set0TemplateData::UPDATE_TEMPLATE_ENTRIES")
+        else {
+            panic!("expected an array of update template entries");
+        };
+
+        // Each entry points at its binding's field of the data structure.
+        let entries: Vec<_> = entries
+            .elems
+            .iter()
+            .map(|entry| {
+                let syn::Expr::Struct(entry) = entry else {
+                    panic!("expected a DescriptorUpdateTemplateEntry");
+                };
+                let field = |name: &str| {
+                    entry
+                        .fields
+                        .iter()
+                        .find(|field| matches!(&field.member, syn::Member::Named(ident) if ident == name))
+                        .map(|field| field.expr.clone())
+                        .unwrap()
+                };
+                (field("dst_binding"), field("offset"))
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (
+                    syn::parse_quote! {0u32},
+                    syn::parse_quote! {core::mem::offset_of!(Set0TemplateData, material)}
+                ),
+                (
+                    syn::parse_quote! {1u32},
+                    syn::parse_quote! {core::mem::offset_of!(Set0TemplateData, albedo)}
+                ),
+                (
+                    syn::parse_quote! {2u32},
+                    syn::parse_quote! {core::mem::offset_of!(Set0TemplateData, normal)}
+                ),
+            ]
+        );
+
+        let create_info = find_fn(&items, "Set0TemplateData::update_template_create_info").unwrap();
+        assert_eq!(parameter_names(&create_info), ["layout"]);
+        let update = find_fn(&items, "Set0TemplateData::update").unwrap();
+        assert_eq!(
+            parameter_names(&update),
+            ["self", "device", "set", "template"]
+        );
+    }
+
+    /// A push descriptor set of sampled images, one per binding.
+    fn push_descriptor_sets(binding_count: u32) -> DescriptorSets {
+        let mut builder = Builder::new();
+        let image = sampled_image(&mut builder);
+        for binding in 0..binding_count {
+            let name = format!("texture_{binding}_push");
+            variable(
+                &mut builder,
+                image,
+                StorageClass::UniformConstant,
+                &name,
+                0,
+                binding,
+            );
+        }
+        let spirv = load_built_module(builder);
+
+        DescriptorSets::from_spirv(&spirv).unwrap()
+    }
+
+    #[test]
+    fn push_descriptor_set() {
+        let descriptor_sets = push_descriptor_sets(2);
+        descriptor_sets.validate().unwrap();
+        assert_eq!(descriptor_sets.push_descriptor_sets, [0]);
+
+        let items = parse_items(descriptor_sets.to_token_stream());
+
+        // Push descriptor sets are never allocated.
+        assert!(find_fn(&items, "set_0_pool_sizes").is_none());
+
+        let set_layouts = find_fn(&items, "set_layouts").unwrap();
+        assert_eq!(
+            method_arguments(&set_layouts, "flags"),
+            [[syn::parse_quote! {ash::vk::DescriptorSetLayoutCreateFlags::PUSH_DESCRIPTOR_KHR}]]
+        );
+
+        let push = find_fn(&items, "Set0Writer::cmd_push_descriptor_set").unwrap();
+        assert_eq!(
+            parameter_names(&push),
+            ["self", "device", "command_buffer", "bind_point", "layout"]
+        );
+        let create_info = find_fn(&items, "Set0TemplateData::update_template_create_info").unwrap();
+        assert_eq!(parameter_names(&create_info), ["bind_point", "layout"]);
+    }
+
+    #[test]
+    fn push_descriptor_limits() {
+        push_descriptor_sets(32).validate().unwrap();
+
+        assert!(matches!(
+            push_descriptor_sets(33).validate(),
+            Err(DescriptorSetError::TooManyPushDescriptors { set: 0, count: 33 })
+        ));
+    }
+
+    #[test]
+    fn multiple_push_descriptor_sets() {
+        let mut builder = Builder::new();
+        let image = sampled_image(&mut builder);
+        for set in [0, 1] {
+            variable(
+                &mut builder,
+                image,
+                StorageClass::UniformConstant,
+                "texture_push",
+                set,
+                0,
+            );
+        }
+        let spirv = load_built_module(builder);
+
+        let descriptor_sets = DescriptorSets::from_spirv(&spirv).unwrap();
+
+        assert!(matches!(
+            descriptor_sets.validate(),
+            Err(DescriptorSetError::MultiplePushDescriptorSets(sets)) if sets == [0, 1]
+        ));
+    }
+
+    #[test]
+    fn descriptor_buffer_layout() {
+        let items = parse_items(material_sets().to_token_stream());
+
+        let layout = find_struct(&items, "Set0DescriptorBufferLayout").unwrap();
+        let fields: Vec<_> = struct_fields(&layout)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "size",
+                "material_offset",
+                "albedo_offset",
+                "normal_offset",
+                "material_size",
+                "albedo_size",
+                "normal_size"
+            ]
+        );
+
+        let write = find_fn(&items, "Set0DescriptorBufferLayout::write_albedo").unwrap();
+        assert_eq!(
+            parameter_names(&write),
+            ["self", "device", "set_data", "data"]
+        );
+        assert_eq!(
+            method_arguments(&write, "ty"),
+            [[syn::parse_quote! {ash::vk::DescriptorType::SAMPLED_IMAGE}]]
+        );
+
+        // Push descriptors are not stored in a descriptor buffer.
+        let items = parse_items(push_descriptor_sets(1).to_token_stream());
+        assert!(find_struct(&items, "Set0DescriptorBufferLayout").is_none());
+    }
+
+    #[test]
+    fn dynamic_buffers() {
+        let mut builder = Builder::new();
+        let block = uniform_block(&mut builder);
+        let image = sampled_image(&mut builder);
+        variable(
+            &mut builder,
+            block,
+            StorageClass::StorageBuffer,
+            "particles_dynamic",
+            0,
+            0,
+        );
+        variable(
+            &mut builder,
+            image,
+            StorageClass::UniformConstant,
+            "albedo_dynamic",
+            0,
+            1,
+        );
+        let spirv = load_built_module(builder);
+
+        let descriptor_sets = DescriptorSets::from_spirv(&spirv).unwrap();
+        descriptor_sets.validate().unwrap();
+
+        // Only buffers have dynamic descriptor types.
+        let bindings = descriptor_sets.set_bindings(0);
+        assert_eq!(
+            bindings[0].binding_type,
+            DescriptorType::StorageBufferDynamic
+        );
+        assert_eq!(bindings[1].binding_type, DescriptorType::SampledImage);
+
+        let items = parse_items(descriptor_sets.to_token_stream());
+        let pool_sizes = find_fn(&items, "pool_sizes").unwrap();
+        assert_eq!(
+            method_arguments(&pool_sizes, "ty"),
+            [
+                [syn::parse_quote! {ash::vk::DescriptorType::SAMPLED_IMAGE}],
+                [syn::parse_quote! {ash::vk::DescriptorType::STORAGE_BUFFER_DYNAMIC}],
+            ]
+        );
+
+        let dynamic_offset = find_fn(&items, "dynamic_offset").unwrap();
+        assert_eq!(
+            parameter_names(&dynamic_offset),
+            ["element_size", "index", "min_offset_alignment"]
+        );
+
+        // Dynamic descriptors cannot be used with descriptor buffers.
+        let layout = find_struct(&items, "Set0DescriptorBufferLayout").unwrap();
+        assert!(
+            struct_fields(&layout)
+                .iter()
+                .all(|(name, _)| !name.starts_with("particles"))
+        );
+    }
+
+    #[test]
+    fn dynamic_buffer_in_push_descriptor_set() {
+        let mut builder = Builder::new();
+        let block = uniform_block(&mut builder);
+        let image = sampled_image(&mut builder);
+        variable(
+            &mut builder,
+            image,
+            StorageClass::UniformConstant,
+            "albedo_push",
+            0,
+            0,
+        );
+        variable(
+            &mut builder,
+            block,
+            StorageClass::Uniform,
+            "camera_dynamic",
+            0,
+            1,
+        );
+        let spirv = load_built_module(builder);
+
+        let descriptor_sets = DescriptorSets::from_spirv(&spirv).unwrap();
+
+        assert!(matches!(
+            descriptor_sets.validate(),
+            Err(DescriptorSetError::DynamicBufferInPushDescriptorSet { set: 0, binding: 1 })
+        ));
+    }
+
+    #[test]
+    fn separate_sampler_and_image_are_combined() {
+        let mut builder = Builder::new();
+        let sampler = builder.type_sampler();
+        let image = sampled_image(&mut builder);
+        variable(
+            &mut builder,
+            sampler,
+            StorageClass::UniformConstant,
+            "diffuse_sampler",
+            0,
+            0,
+        );
+        variable(
+            &mut builder,
+            image,
+            StorageClass::UniformConstant,
+            "diffuse",
+            0,
+            0,
+        );
+        let spirv = load_built_module(builder);
+
+        let descriptor_sets = DescriptorSets::from_spirv(&spirv).unwrap();
+        descriptor_sets.validate().unwrap();
+
+        let bindings = &descriptor_sets.sets[&0];
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].name, "diffuse");
+        assert_eq!(
+            bindings[0].binding_type,
+            DescriptorType::CombinedImageSampler
+        );
+    }
+
+    #[test]
+    fn conflicting_binding_types() {
+        let mut builder = Builder::new();
+        let block = uniform_block(&mut builder);
+        let image = sampled_image(&mut builder);
+        variable(&mut builder, block, StorageClass::Uniform, "camera", 1, 2);
+        variable(
+            &mut builder,
+            image,
+            StorageClass::UniformConstant,
+            "albedo",
+            1,
+            2,
+        );
+        let spirv = load_built_module(builder);
+
+        let descriptor_sets = DescriptorSets::from_spirv(&spirv).unwrap();
+
+        assert!(matches!(
+            descriptor_sets.validate(),
+            Err(DescriptorSetError::ConflictingBindingTypes {
+                set: 1,
+                binding: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn immutable_samplers() {
+        let mut builder = Builder::new();
+        let sampler = builder.type_sampler();
+        let image = sampled_image(&mut builder);
+        variable(
+            &mut builder,
+            sampler,
+            StorageClass::UniformConstant,
+            "linear_immutable",
+            0,
+            0,
+        );
+        variable(
+            &mut builder,
+            sampler,
+            StorageClass::UniformConstant,
+            "shadow_immutable",
+            0,
+            1,
+        );
+        variable(
+            &mut builder,
+            image,
+            StorageClass::UniformConstant,
+            "shadow_map",
+            0,
+            1,
+        );
+        let spirv = load_built_module(builder);
+
+        let descriptor_sets = DescriptorSets::from_spirv(&spirv).unwrap();
+        descriptor_sets.validate().unwrap();
+
+        // The sampler of a combined binding may be the immutable one.
+        let bindings = descriptor_sets.set_bindings(0);
+        assert!(bindings[0].immutable_samplers);
+        assert!(bindings[1].immutable_samplers);
+        assert_eq!(
+            bindings[1].binding_type,
+            DescriptorType::CombinedImageSampler
+        );
+
+        let items = parse_items(descriptor_sets.to_token_stream());
+
+        let set_layouts = find_fn(&items, "set_layouts").unwrap();
+        assert_eq!(
+            parameter_names(&set_layouts),
+            [
+                "device",
+                "flags",
+                "set_0_linear_immutable_samplers",
+                "set_0_shadow_map_samplers"
+            ]
+        );
+        let syn::FnArg::Typed(samplers) = &set_layouts.sig.inputs[2] else {
+            panic!("expected a typed parameter");
+        };
+        assert_eq!(
+            *samplers.ty,
+            syn::parse_quote! {&[ash::vk::Sampler; 1usize]}
+        );
+        assert_eq!(
+            method_arguments(&set_layouts, "immutable_samplers"),
+            [
+                [syn::parse_quote! {set_0_linear_immutable_samplers}],
+                [syn::parse_quote! {set_0_shadow_map_samplers}],
+            ]
+        );
+
+        // Bindings of only immutable samplers are never written.
+        let writer = find_struct(&items, "Set0Writer").unwrap();
+        let fields: Vec<_> = struct_fields(&writer)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(fields, ["shadow_map"]);
+    }
+
+    #[test]
+    fn inline_uniform_blocks() {
+        let mut builder = Builder::new();
+        let block = uniform_block(&mut builder);
+        variable(
+            &mut builder,
+            block,
+            StorageClass::Uniform,
+            "settings_inline",
+            0,
+            0,
+        );
+        variable(
+            &mut builder,
+            block,
+            StorageClass::StorageBuffer,
+            "particles_inline",
+            0,
+            1,
+        );
+        let spirv = load_built_module(builder);
+
+        let descriptor_sets = DescriptorSets::from_spirv(&spirv).unwrap();
+        descriptor_sets.validate().unwrap();
+
+        // Only uniform blocks can be inline, other bindings keep their type.
+        let bindings = descriptor_sets.set_bindings(0);
+        assert_eq!(bindings[0].binding_type, DescriptorType::InlineUniformBlock);
+        assert_eq!(bindings[0].descriptor_count, 16);
+        assert_eq!(bindings[1].binding_type, DescriptorType::StorageBuffer);
+        assert_eq!(bindings[1].descriptor_count, 1);
+
+        let items = parse_items(descriptor_sets.to_token_stream());
+
+        let write = find_fn(&items, "Set0DescriptorBufferLayout::write_settings_inline").unwrap();
+        let syn::FnArg::Typed(data) = &write.sig.inputs[2] else {
+            panic!("expected a typed parameter");
+        };
+        assert_eq!(*data.ty, syn::parse_quote! {&[u8; 16usize]});
+
+        let pool_info = find_fn(&items, "inline_uniform_block_pool_info").unwrap();
+        assert_eq!(
+            method_arguments(&pool_info, "max_inline_uniform_block_bindings"),
+            [[syn::parse_quote! {1u32 * sets_per_layout}]]
+        );
+        assert!(find_fn(&items, "within_inline_uniform_block_limits").is_some());
+    }
+
+    #[test]
+    fn input_attachment_indices() {
+        let mut builder = Builder::new();
+        let float = builder.type_float(32);
+        for (name, depth, binding, index) in [("normal", 0, 0, 2), ("depth", 1, 1, 0)] {
+            let subpass = builder.type_image(
+                float,
+                Dim::DimSubpassData,
+                depth,
+                0,
+                0,
+                2,
+                ImageFormat::Unknown,
+                None,
+            );
+            let attachment = variable(
+                &mut builder,
+                subpass,
+                StorageClass::UniformConstant,
+                name,
+                0,
+                binding,
+            );
+            builder.decorate(
+                attachment,
+                Decoration::InputAttachmentIndex,
+                [Operand::LiteralBit32(index)],
+            );
+        }
+        let spirv = load_built_module(builder);
+
+        let descriptor_sets = DescriptorSets::from_spirv(&spirv).unwrap();
+        descriptor_sets.validate().unwrap();
+
+        let items = parse_items(descriptor_sets.to_token_stream());
+        assert_eq!(
+            find_const(&items, "SET_0_NORMAL_INPUT_ATTACHMENT_INDEX"),
+            Some(syn::parse_quote! {2u32})
+        );
+        assert_eq!(
+            find_const(&items, "INPUT_ATTACHMENT_INDICES"),
+            Some(syn::parse_quote! {[0u32, 2u32]})
+        );
+        assert_eq!(
+            find_const(&items, "DEPTH_INPUT_ATTACHMENT_INDEX"),
+            Some(syn::parse_quote! {Some(0u32)})
+        );
+
+        // The attachments feeding each index are mapped by the caller.
+        let info = find_fn(&items, "rendering_input_attachment_index_info").unwrap();
+        assert_eq!(
+            parameter_names(&info),
+            [
+                "color_attachment_input_indices",
+                "depth_input_attachment_index",
+                "stencil_input_attachment_index"
+            ]
+        );
+    }
+
+    #[test]
+    fn image_metadata() {
+        let mut builder = Builder::new();
+        let float = builder.type_float(32);
+        let cube_array =
+            builder.type_image(float, Dim::DimCube, 0, 1, 0, 1, ImageFormat::Unknown, None);
+        let shadow = builder.type_image(float, Dim::Dim2D, 1, 0, 1, 1, ImageFormat::Unknown, None);
+        let texel_buffer = builder.type_image(
+            float,
+            Dim::DimBuffer,
+            0,
+            0,
+            0,
+            1,
+            ImageFormat::Unknown,
+            None,
+        );
+        for (image, name, binding) in [
+            (cube_array, "environment", 0),
+            (shadow, "shadow", 1),
+            (texel_buffer, "weights", 2),
+        ] {
+            variable(
+                &mut builder,
+                image,
+                StorageClass::UniformConstant,
+                name,
+                0,
+                binding,
+            );
+        }
+        let spirv = load_built_module(builder);
+
+        let descriptor_sets = DescriptorSets::from_spirv(&spirv).unwrap();
+
+        let bindings = descriptor_sets.set_bindings(0);
+        let shadow = bindings[1].image.as_ref().unwrap();
+        assert!(shadow.depth && shadow.multisampled && !shadow.arrayed);
+
+        let items = parse_items(descriptor_sets.to_token_stream());
+        assert_eq!(
+            find_const(&items, "SET_0_ENVIRONMENT_VIEW_TYPE"),
+            Some(syn::parse_quote! {ash::vk::ImageViewType::CUBE_ARRAY})
+        );
+        assert_eq!(
+            find_const(&items, "SET_0_SHADOW_VIEW_TYPE"),
+            Some(syn::parse_quote! {ash::vk::ImageViewType::TYPE_2D})
+        );
+        assert_eq!(
+            find_const(&items, "SET_0_SHADOW_MULTISAMPLED"),
+            Some(syn::parse_quote! {true})
+        );
+        assert_eq!(
+            find_const(&items, "SET_0_SHADOW_DEPTH"),
+            Some(syn::parse_quote! {true})
+        );
+
+        // Texel buffers are bound as buffer views, which have no view type.
+        assert!(find_const(&items, "SET_0_WEIGHTS_VIEW_TYPE").is_none());
+        assert!(find_const(&items, "SET_0_WEIGHTS_MULTISAMPLED").is_none());
+    }
+
+    #[test]
+    fn storage_image_formats() {
+        let mut builder = Builder::new();
+        let float = builder.type_float(32);
+        let rgba8 = builder.type_image(float, Dim::Dim2D, 0, 0, 0, 2, ImageFormat::Rgba8, None);
+        let unknown = builder.type_image(float, Dim::Dim2D, 0, 0, 0, 2, ImageFormat::Unknown, None);
+        variable(
+            &mut builder,
+            rgba8,
+            StorageClass::UniformConstant,
+            "output",
+            0,
+            0,
+        );
+        let input = variable(
+            &mut builder,
+            unknown,
+            StorageClass::UniformConstant,
+            "input",
+            0,
+            1,
+        );
+        builder.decorate(input, Decoration::NonWritable, []);
+        let spirv = load_built_module(builder);
+
+        let descriptor_sets = DescriptorSets::from_spirv(&spirv).unwrap();
+        let items = parse_items(descriptor_sets.to_token_stream());
+
+        assert_eq!(
+            find_const(&items, "SET_0_OUTPUT_REQUIRED_FORMAT"),
+            Some(syn::parse_quote! {Some(ash::vk::Format::R8G8B8A8_UNORM)})
+        );
+        assert_eq!(
+            find_const(&items, "SET_0_INPUT_REQUIRED_FORMAT"),
+            Some(syn::parse_quote! {None})
+        );
+
+        let check = find_fn(&items, "set_0_output_supports_format").unwrap();
+        assert_eq!(parameter_names(&check), ["format", "features"]);
+        assert_eq!(
+            accessed_fields(&check),
+            [
+                "shader_storage_image_read_without_format",
+                "shader_storage_image_write_without_format"
+            ]
+        );
+
+        // Images that are only read need only support reading without a format.
+        let check = find_fn(&items, "set_0_input_supports_format").unwrap();
+        assert_eq!(
+            accessed_fields(&check),
+            ["shader_storage_image_read_without_format"]
+        );
+    }
+}
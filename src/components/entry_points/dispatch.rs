@@ -1,45 +1,429 @@
-use quote::{ToTokens, quote};
+use convert_case::{Case, Casing};
+use proc_macro2::TokenStream;
+use quote::{ToTokens, format_ident, quote};
 use rspirv::dr::Module;
-use spirv::{ExecutionMode, Op};
+use spirv::{BuiltIn, Decoration, ExecutionMode, ExecutionModel, Op};
+
+use crate::{
+    components::specialization_constants::SpecializationConstant,
+    types::{FromInstruction, Scalar, Type},
+    utilities::find_instruction_with_id,
+};
+
+/// A single dimension of a workgroup size.
+pub enum Dimension {
+    Literal(u32),
+    Specialized(SpecializationConstant),
+}
+
+impl Dimension {
+    pub fn from_id(id: u32, spirv: &Module) -> Option<Self> {
+        let instruction = find_instruction_with_id(id, spirv)?;
+
+        match instruction.class.opcode {
+            // OpConstant | Result Type: <id> | Result: <id> | Value: Literal
+            Op::Constant => Some(Self::Literal(
+                instruction.operands[0].unwrap_literal_bit32(),
+            )),
+
+            // OpSpecConstant | Result Type: <id> | Result: <id> | Value: Literal
+            Op::SpecConstant => {
+                match SpecializationConstant::from_instruction(instruction, spirv) {
+                    Some(constant) => Some(Self::Specialized(constant)),
+
+                    // Without a SpecId the constant cannot be specialized, so it keeps its default.
+                    None => Some(Self::Literal(
+                        instruction.operands[0].unwrap_literal_bit32(),
+                    )),
+                }
+            }
+
+            _ => None,
+        }
+    }
+
+    pub fn is_specialized(&self) -> bool {
+        matches!(self, Self::Specialized(_))
+    }
+}
+
+impl ToTokens for Dimension {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let new_tokens = match self {
+            Self::Literal(value) => quote! {#value},
+
+            Self::Specialized(constant) => {
+                let field = format_ident!("{}", constant.name.to_case(Case::Snake));
+
+                if matches!(constant.constant_type, Type::Scalar(Scalar::U32)) {
+                    quote! {spec.#field}
+                } else {
+                    quote! {spec.#field as u32}
+                }
+            }
+        };
+
+        tokens.extend(new_tokens);
+    }
+}
 
 pub struct Dispatch {
-    pub x: u32,
-    pub y: u32,
-    pub z: u32,
+    pub x: Dimension,
+    pub y: Dimension,
+    pub z: Dimension,
 }
 
 impl Dispatch {
-    pub fn for_entrypoint(entry_point_id: u32, spirv: &Module) -> Option<Self> {
+    pub fn for_entrypoint(
+        entry_point_id: u32,
+        execution_model: ExecutionModel,
+        spirv: &Module,
+    ) -> Option<Self> {
+        let local_size = Self::from_execution_modes(entry_point_id, spirv);
+
+        // A WorkgroupSize BuiltIn takes precedence over any LocalSize or LocalSizeId.
+        let uses_workgroup_size = local_size.is_some()
+            || matches!(
+                execution_model,
+                ExecutionModel::GLCompute | ExecutionModel::TaskEXT | ExecutionModel::MeshEXT
+            );
+        if uses_workgroup_size {
+            if let Some(dispatch) = Self::from_workgroup_size(spirv) {
+                return Some(dispatch);
+            }
+        }
+
+        local_size
+    }
+
+    fn from_execution_modes(entry_point_id: u32, spirv: &Module) -> Option<Self> {
         spirv.execution_modes.iter().find_map(|mode| {
             // OpExecutionMode | Entry Point: <id> | Mode: Execution Mode | Literal...
+            // OpExecutionModeId | Entry Point: <id> | Mode: Execution Mode | <id>...
 
-            if !matches!(mode.class.opcode, Op::ExecutionMode) {
+            if mode.operands[0].unwrap_id_ref() != entry_point_id {
                 return None;
             }
 
-            if mode.operands[0].unwrap_id_ref() != entry_point_id {
+            match (mode.class.opcode, mode.operands[1].unwrap_execution_mode()) {
+                (Op::ExecutionMode, ExecutionMode::LocalSize) => {
+                    let x = mode.operands[2].unwrap_literal_bit32();
+                    let y = mode.operands[3].unwrap_literal_bit32();
+                    let z = mode.operands[4].unwrap_literal_bit32();
+
+                    Some(Self {
+                        x: Dimension::Literal(x),
+                        y: Dimension::Literal(y),
+                        z: Dimension::Literal(z),
+                    })
+                }
+
+                (Op::ExecutionModeId, ExecutionMode::LocalSizeId) => {
+                    let x = Dimension::from_id(mode.operands[2].unwrap_id_ref(), spirv)?;
+                    let y = Dimension::from_id(mode.operands[3].unwrap_id_ref(), spirv)?;
+                    let z = Dimension::from_id(mode.operands[4].unwrap_id_ref(), spirv)?;
+
+                    Some(Self { x, y, z })
+                }
+
+                _ => None,
+            }
+        })
+    }
+
+    fn from_workgroup_size(spirv: &Module) -> Option<Self> {
+        let composite_id = spirv.annotations.iter().find_map(|annotation| {
+            // OpDecorate | Target: <id> | Decoration | Literal...
+
+            if !matches!(annotation.class.opcode, Op::Decorate) {
                 return None;
             }
 
-            // TODO LocalSizeHint
-            if mode.operands[1].unwrap_execution_mode() != ExecutionMode::LocalSize {
+            if annotation.operands[1].unwrap_decoration() != Decoration::BuiltIn {
                 return None;
             }
 
-            let x = mode.operands[2].unwrap_literal_bit32();
-            let y = mode.operands[3].unwrap_literal_bit32();
-            let z = mode.operands[4].unwrap_literal_bit32();
+            if annotation.operands[2].unwrap_built_in() != BuiltIn::WorkgroupSize {
+                return None;
+            }
 
-            Some(Self { x, y, z })
-        })
+            Some(annotation.operands[0].unwrap_id_ref())
+        })?;
+
+        // Op*ConstantComposite | Result Type: <id> | Result: <id> | Constituents: <id>...
+        let composite = find_instruction_with_id(composite_id, spirv)?;
+
+        if !matches!(
+            composite.class.opcode,
+            Op::ConstantComposite | Op::SpecConstantComposite
+        ) {
+            return None;
+        }
+
+        let x = Dimension::from_id(composite.operands[0].unwrap_id_ref(), spirv)?;
+        let y = Dimension::from_id(composite.operands[1].unwrap_id_ref(), spirv)?;
+        let z = Dimension::from_id(composite.operands[2].unwrap_id_ref(), spirv)?;
+
+        Some(Self { x, y, z })
+    }
+
+    pub fn is_specialized(&self) -> bool {
+        self.x.is_specialized() || self.y.is_specialized() || self.z.is_specialized()
+    }
+
+    /// The parameter and argument tokens to thread the specialization constants through the
+    /// generated functions, if the dispatch size depends on them.
+    pub fn spec_tokens(&self) -> (TokenStream, TokenStream) {
+        if self.is_specialized() {
+            (
+                quote! {spec: &super::SpecializationConstants,},
+                quote! {spec,},
+            )
+        } else {
+            (TokenStream::new(), TokenStream::new())
+        }
+    }
+
+    /// The expression for the dispatch size inside the entry point's module.
+    pub fn size_tokens(&self) -> TokenStream {
+        if self.is_specialized() {
+            let (_, spec_argument) = self.spec_tokens();
+            quote! {dispatch_size(#spec_argument)}
+        } else {
+            quote! {DISPATCH_SIZE}
+        }
+    }
+
+    /// Tokens for recording a dispatch, only meaningful for compute entry points.
+    pub fn command_tokens(&self) -> TokenStream {
+        let (spec_parameter, spec_argument) = self.spec_tokens();
+
+        quote! {
+            pub unsafe fn cmd_dispatch_for(
+                device: &ash::Device,
+                command_buffer: ash::vk::CommandBuffer,
+                #spec_parameter
+                extent: [u32; 3],
+            ) {
+                let [x, y, z] = group_count(#spec_argument extent);
+                unsafe { device.cmd_dispatch(command_buffer, x, y, z) };
+            }
+
+            pub const fn dispatch_indirect_command(
+                #spec_parameter
+                extent: [u32; 3],
+            ) -> ash::vk::DispatchIndirectCommand {
+                let [x, y, z] = group_count(#spec_argument extent);
+                ash::vk::DispatchIndirectCommand { x, y, z }
+            }
+        }
     }
 }
 
 impl ToTokens for Dispatch {
-    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
         let Self { x, y, z } = self;
-        let new_tokens = quote! {pub const DISPATCH_SIZE: [u32; 3] = [#x, #y, #z];};
+        let (spec_parameter, _) = self.spec_tokens();
+
+        let size_tokens = if self.is_specialized() {
+            quote! {
+                pub const fn dispatch_size(spec: &super::SpecializationConstants) -> [u32; 3] {
+                    [#x, #y, #z]
+                }
+            }
+        } else {
+            quote! {pub const DISPATCH_SIZE: [u32; 3] = [#x, #y, #z];}
+        };
+
+        let size = self.size_tokens();
+
+        let new_tokens = quote! {
+            #size_tokens
+
+            pub const fn group_count(#spec_parameter extent: [u32; 3]) -> [u32; 3] {
+                let size = #size;
+
+                [
+                    extent[0].div_ceil(size[0]),
+                    extent[1].div_ceil(size[1]),
+                    extent[2].div_ceil(size[2]),
+                ]
+            }
+        };
 
         tokens.extend(new_tokens);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use rspirv::dr::{Builder, Operand};
+    use spirv::{BuiltIn, Decoration, ExecutionMode, ExecutionModel};
+
+    use crate::utilities::{
+        called_names, find_const, find_fn, load_built_module, parameter_names, parse_items,
+        referenced_names,
+    };
+
+    use super::{Dimension, Dispatch};
+
+    fn literals(dispatch: &Dispatch) -> Option<[u32; 3]> {
+        match (&dispatch.x, &dispatch.y, &dispatch.z) {
+            (Dimension::Literal(x), Dimension::Literal(y), Dimension::Literal(z)) => {
+                Some([*x, *y, *z])
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn local_size() {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        builder.entry_point(ExecutionModel::GLCompute, main, "main", []);
+        builder.execution_mode(main, ExecutionMode::LocalSize, [8, 4, 1]);
+        let spirv = load_built_module(builder);
+
+        let dispatch = Dispatch::for_entrypoint(main, ExecutionModel::GLCompute, &spirv).unwrap();
+        assert_eq!(literals(&dispatch), Some([8, 4, 1]));
+
+        let items = parse_items(dispatch.to_token_stream());
+        assert_eq!(
+            find_const(&items, "DISPATCH_SIZE"),
+            Some(syn::parse_quote! {[8u32, 4u32, 1u32]})
+        );
+    }
+
+    #[test]
+    fn group_count_helpers() {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        builder.entry_point(ExecutionModel::GLCompute, main, "main", []);
+        builder.execution_mode(main, ExecutionMode::LocalSize, [8, 8, 1]);
+        let spirv = load_built_module(builder);
+
+        let dispatch = Dispatch::for_entrypoint(main, ExecutionModel::GLCompute, &spirv).unwrap();
+        let mut tokens = dispatch.to_token_stream();
+        tokens.extend(dispatch.command_tokens());
+        let items = parse_items(tokens);
+
+        let group_count = find_fn(&items, "group_count").unwrap();
+        assert!(group_count.sig.constness.is_some());
+        assert_eq!(parameter_names(&group_count), ["extent"]);
+        assert!(referenced_names(&group_count).contains(&"DISPATCH_SIZE".to_string()));
+        assert!(called_names(&group_count).contains(&"div_ceil".to_string()));
+
+        let cmd_dispatch_for = find_fn(&items, "cmd_dispatch_for").unwrap();
+        assert_eq!(
+            parameter_names(&cmd_dispatch_for),
+            ["device", "command_buffer", "extent"]
+        );
+        assert_eq!(
+            called_names(&cmd_dispatch_for),
+            ["group_count", "cmd_dispatch"]
+        );
+
+        let indirect = find_fn(&items, "dispatch_indirect_command").unwrap();
+        assert!(indirect.sig.constness.is_some());
+        assert_eq!(called_names(&indirect), ["group_count"]);
+    }
+
+    #[test]
+    fn specialized_group_count_helpers() {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        let uint = builder.type_int(32, 0);
+        let x = builder.spec_constant_bit32(uint, 64);
+        let y = builder.constant_bit32(uint, 1);
+        builder.name(x, "group_width");
+        builder.decorate(x, Decoration::SpecId, [Operand::LiteralBit32(0)]);
+        builder.entry_point(ExecutionModel::GLCompute, main, "main", []);
+        builder.execution_mode_id(main, ExecutionMode::LocalSizeId, [x, y, y]);
+        let spirv = load_built_module(builder);
+
+        let dispatch = Dispatch::for_entrypoint(main, ExecutionModel::GLCompute, &spirv).unwrap();
+        let mut tokens = dispatch.to_token_stream();
+        tokens.extend(dispatch.command_tokens());
+        let items = parse_items(tokens);
+
+        let group_count = find_fn(&items, "group_count").unwrap();
+        assert_eq!(parameter_names(&group_count), ["spec", "extent"]);
+        assert!(called_names(&group_count).contains(&"dispatch_size".to_string()));
+
+        let cmd_dispatch_for = find_fn(&items, "cmd_dispatch_for").unwrap();
+        assert_eq!(
+            parameter_names(&cmd_dispatch_for),
+            ["device", "command_buffer", "spec", "extent"]
+        );
+    }
+
+    #[test]
+    fn local_size_id_with_specialization_constant() {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        let uint = builder.type_int(32, 0);
+        let x = builder.spec_constant_bit32(uint, 64);
+        let y = builder.constant_bit32(uint, 2);
+        let z = builder.constant_bit32(uint, 1);
+        builder.name(x, "group_width");
+        builder.decorate(x, Decoration::SpecId, [Operand::LiteralBit32(0)]);
+        builder.entry_point(ExecutionModel::GLCompute, main, "main", []);
+        builder.execution_mode_id(main, ExecutionMode::LocalSizeId, [x, y, z]);
+        let spirv = load_built_module(builder);
+
+        let dispatch = Dispatch::for_entrypoint(main, ExecutionModel::GLCompute, &spirv).unwrap();
+
+        assert!(dispatch.is_specialized());
+        assert!(
+            matches!(&dispatch.x, Dimension::Specialized(constant) if constant.name == "group_width")
+        );
+        assert!(matches!(dispatch.y, Dimension::Literal(2)));
+        assert!(matches!(dispatch.z, Dimension::Literal(1)));
+
+        // The size depends on the constants, so it is a function of them rather than a constant.
+        let items = parse_items(dispatch.to_token_stream());
+        assert!(find_const(&items, "DISPATCH_SIZE").is_none());
+        assert!(find_fn(&items, "dispatch_size").is_some());
+    }
+
+    #[test]
+    fn spec_constant_without_spec_id_keeps_its_default() {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        let uint = builder.type_int(32, 0);
+        let x = builder.spec_constant_bit32(uint, 16);
+        let y = builder.constant_bit32(uint, 1);
+        builder.entry_point(ExecutionModel::GLCompute, main, "main", []);
+        builder.execution_mode_id(main, ExecutionMode::LocalSizeId, [x, y, y]);
+        let spirv = load_built_module(builder);
+
+        let dispatch = Dispatch::for_entrypoint(main, ExecutionModel::GLCompute, &spirv).unwrap();
+
+        assert!(!dispatch.is_specialized());
+        assert_eq!(literals(&dispatch), Some([16, 1, 1]));
+    }
+
+    #[test]
+    fn workgroup_size_overrides_local_size() {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        let uint = builder.type_int(32, 0);
+        let uvec3 = builder.type_vector(uint, 3);
+        let x = builder.constant_bit32(uint, 32);
+        let y = builder.constant_bit32(uint, 1);
+        let size = builder.constant_composite(uvec3, [x, y, y]);
+        builder.decorate(
+            size,
+            Decoration::BuiltIn,
+            [Operand::BuiltIn(BuiltIn::WorkgroupSize)],
+        );
+        builder.entry_point(ExecutionModel::GLCompute, main, "main", []);
+        builder.execution_mode(main, ExecutionMode::LocalSize, [8, 8, 1]);
+        let spirv = load_built_module(builder);
+
+        let dispatch = Dispatch::for_entrypoint(main, ExecutionModel::GLCompute, &spirv).unwrap();
+
+        assert_eq!(literals(&dispatch), Some([32, 1, 1]));
+    }
+}
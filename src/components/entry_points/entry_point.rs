@@ -1,28 +1,45 @@
 use core::ffi::CStr;
 
 use convert_case::{Case, Casing};
+use itertools::Itertools;
 use quote::{ToTokens, format_ident, quote};
+use rspirv::dr::Module;
 use spirv::{ExecutionModel, Op};
 
 use crate::{
+    components::{FromSpirv, PushConstants, SpecializationConstants},
     types::FromInstruction,
-    utilities::{execution_model_to_string, execution_model_to_tokens},
+    utilities::{execution_model_to_string, execution_model_to_tokens, execution_models_to_tokens},
 };
 
-use super::{dispatch::Dispatch, vertex_inputs::VertexInputs};
+use super::{
+    dispatch::Dispatch, fragment::Fragment, geometry::Geometry, kernel::Kernel,
+    mesh_shading::MeshShading, ray_tracing_interface::RayTracingInterface,
+    tessellation::Tessellation, vertex_inputs::VertexInputs,
+};
 
 pub struct EntryPoint {
     pub name: String,
     pub execution_model: ExecutionModel,
     pub dispatch: Option<Dispatch>,
+    pub mesh_shading: Option<MeshShading>,
+    pub geometry: Option<Geometry>,
+    pub tessellation: Option<Tessellation>,
+    pub fragment: Option<Fragment>,
+    pub ray_tracing: Option<RayTracingInterface>,
+    pub kernel: Option<Kernel>,
     pub vertex_inputs: Option<VertexInputs>,
+    /// The stages the entry point may be linked with, those of its module unless linked with
+    /// other modules.
+    pub linked_stages: Vec<ExecutionModel>,
+    /// Whether the entry point's own module has a task shader, linked modules aside.
+    pub has_task_shader: bool,
+    pub has_specialization_constants: bool,
+    pub has_push_constants: bool,
 }
 
 impl FromInstruction for EntryPoint {
-    fn from_instruction(
-        instruction: &rspirv::dr::Instruction,
-        spirv: &rspirv::dr::Module,
-    ) -> Option<Self> {
+    fn from_instruction(instruction: &rspirv::dr::Instruction, spirv: &Module) -> Option<Self> {
         // OpEntryPoint | Execution Model | Entry Point: <id> | Name: Literal | <id>...
 
         if !matches!(instruction.class.opcode, Op::EntryPoint) {
@@ -33,50 +50,255 @@ impl FromInstruction for EntryPoint {
         let entry_point_id = instruction.operands[1].unwrap_id_ref();
         let name = instruction.operands[2].unwrap_literal_string().to_string();
 
-        let dispatch = Dispatch::for_entrypoint(entry_point_id, spirv);
+        let dispatch = Dispatch::for_entrypoint(entry_point_id, execution_model, spirv);
+
+        let mesh_shading = MeshShading::from_instruction(instruction, spirv);
+        let geometry = Geometry::from_instruction(instruction, spirv);
+        let tessellation = Tessellation::from_instruction(instruction, spirv);
+        let fragment = Fragment::from_instruction(instruction, spirv);
+        let ray_tracing = RayTracingInterface::from_instruction(instruction, spirv);
+        let kernel = Kernel::from_instruction(instruction, spirv);
 
         let vertex_inputs = VertexInputs::from_instruction(instruction, spirv, None); // TODO
 
+        // OpEntryPoint | Execution Model | Entry Point: <id> | Name: Literal | <id>...
+        let linked_stages: Vec<_> = spirv
+            .entry_points
+            .iter()
+            .map(|instruction| instruction.operands[0].unwrap_execution_model())
+            .unique()
+            .collect();
+        let has_task_shader = linked_stages.contains(&ExecutionModel::TaskEXT);
+
+        let has_specialization_constants = SpecializationConstants::from_spirv(spirv).is_some();
+        let has_push_constants = PushConstants::from_spirv(spirv).is_some();
+
         Some(Self {
             name,
             execution_model,
             dispatch,
+            mesh_shading,
+            geometry,
+            tessellation,
+            fragment,
+            ray_tracing,
+            kernel,
             vertex_inputs,
+            linked_stages,
+            has_task_shader,
+            has_specialization_constants,
+            has_push_constants,
         })
     }
 }
 
-impl ToTokens for EntryPoint {
-    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-        let module_name = {
-            let execution_model_name = execution_model_to_string(&self.execution_model);
-            let entrypoint_name = self.name.to_case(Case::Snake);
-
-            if entrypoint_name.starts_with(execution_model_name) {
-                format_ident!("{}", entrypoint_name)
-            } else {
-                format_ident!(
-                    "{}_{}",
-                    execution_model_name,
-                    self.name.to_case(Case::Snake)
-                )
+impl EntryPoint {
+    /// The linked stages that can directly follow this entry point.
+    fn next_stages(&self) -> Vec<ExecutionModel> {
+        let followers: &[ExecutionModel] = match self.execution_model {
+            ExecutionModel::Vertex => &[
+                ExecutionModel::TessellationControl,
+                ExecutionModel::Geometry,
+                ExecutionModel::Fragment,
+            ],
+            ExecutionModel::TessellationControl => &[ExecutionModel::TessellationEvaluation],
+            ExecutionModel::TessellationEvaluation => {
+                &[ExecutionModel::Geometry, ExecutionModel::Fragment]
             }
+            ExecutionModel::Geometry => &[ExecutionModel::Fragment],
+            ExecutionModel::TaskEXT => &[ExecutionModel::MeshEXT],
+            ExecutionModel::MeshEXT => &[ExecutionModel::Fragment],
+            _ => &[],
         };
 
+        self.linked_stages
+            .iter()
+            .filter(|execution_model| followers.contains(execution_model))
+            .copied()
+            .collect()
+    }
+
+    fn shader_object_tokens(&self) -> Option<proc_macro2::TokenStream> {
+        // Stages that can be created as shader objects.
+        if !matches!(
+            self.execution_model,
+            ExecutionModel::Vertex
+                | ExecutionModel::TessellationControl
+                | ExecutionModel::TessellationEvaluation
+                | ExecutionModel::Geometry
+                | ExecutionModel::Fragment
+                | ExecutionModel::GLCompute
+                | ExecutionModel::TaskEXT
+                | ExecutionModel::MeshEXT
+        ) {
+            return None;
+        }
+
+        let next_stage = execution_models_to_tokens(&self.next_stages());
+
+        // Mesh shaders without a task shader in their module must say so to be bound alone.
+        let flags = (self.execution_model == ExecutionModel::MeshEXT && !self.has_task_shader)
+            .then(|| quote! {.flags(ash::vk::ShaderCreateFlagsEXT::NO_TASK_SHADER)});
+
+        let push_constant_ranges = self
+            .has_push_constants
+            .then(|| quote! {.push_constant_ranges(&super::PUSH_CONSTANT_RANGES)});
+
+        Some(quote! {
+            pub const NEXT_STAGE: ash::vk::ShaderStageFlags = #next_stage;
+
+            pub fn shader_create_info<'a>(
+                set_layouts: &'a [ash::vk::DescriptorSetLayout],
+                spec: Option<&'a ash::vk::SpecializationInfo<'a>>,
+            ) -> ash::vk::ShaderCreateInfoEXT<'a> {
+                let info = ash::vk::ShaderCreateInfoEXT::default()
+                    .stage(STAGE)
+                    .next_stage(NEXT_STAGE)
+                    .code_type(ash::vk::ShaderCodeTypeEXT::SPIRV)
+                    .code(super::BYTES)
+                    .name(ENTRY_POINT)
+                    .set_layouts(set_layouts)
+                    #flags
+                    #push_constant_ranges;
+
+                match spec {
+                    Some(spec) => info.specialization_info(spec),
+                    None => info,
+                }
+            }
+        })
+    }
+
+    fn compute_pipeline_tokens(&self) -> proc_macro2::TokenStream {
+        let (spec_parameter, spec_info, spec_argument) = if self.has_specialization_constants {
+            (
+                quote! {spec: &super::SpecializationConstants,},
+                quote! {
+                    let map_entries = spec.specialization_map();
+                    let specialization_info = ash::vk::SpecializationInfo::default()
+                        .map_entries(&map_entries)
+                        .data(bytemuck::bytes_of(spec));
+                },
+                quote! {Some(&specialization_info)},
+            )
+        } else {
+            (
+                proc_macro2::TokenStream::new(),
+                proc_macro2::TokenStream::new(),
+                quote! {None},
+            )
+        };
+
+        quote! {
+            pub unsafe fn create_compute_pipeline(
+                device: &ash::Device,
+                cache: ash::vk::PipelineCache,
+                layout: ash::vk::PipelineLayout,
+                #spec_parameter
+            ) -> ash::prelude::VkResult<ash::vk::Pipeline> {
+                #spec_info
+
+                let module = unsafe { super::create_shader_module(device)? };
+
+                let create_info = ash::vk::ComputePipelineCreateInfo::default()
+                    .stage(stage_create_info(module, #spec_argument))
+                    .layout(layout);
+
+                let pipelines = unsafe { device.create_compute_pipelines(cache, &[create_info], None) };
+
+                // The module is not needed once the pipeline is created, even if creation failed.
+                unsafe { device.destroy_shader_module(module, None) };
+
+                pipelines
+                    .map(|pipelines| pipelines[0])
+                    .map_err(|(_, error)| error)
+            }
+        }
+    }
+
+    pub fn module_name(&self) -> syn::Ident {
+        let execution_model_name = execution_model_to_string(&self.execution_model);
+        let entrypoint_name = self.name.to_case(Case::Snake);
+
+        if entrypoint_name.starts_with(execution_model_name) {
+            format_ident!("{}", entrypoint_name)
+        } else {
+            format_ident!(
+                "{}_{}",
+                execution_model_name,
+                self.name.to_case(Case::Snake)
+            )
+        }
+    }
+}
+
+impl ToTokens for EntryPoint {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let module_name = self.module_name();
+
         let name_terminated = format!("{}\0", self.name);
         let name_cstr = CStr::from_bytes_until_nul(name_terminated.as_bytes()).unwrap();
 
-        let stage_tokens = execution_model_to_tokens(&self.execution_model);
+        // Kernels are dispatched through OpenCL, so they have no Vulkan shader stage.
+        let stage = execution_model_to_tokens(&self.execution_model).map(|stage_tokens| {
+            quote! {
+                pub const STAGE: ash::vk::ShaderStageFlags = #stage_tokens;
+
+                pub fn stage_create_info<'a>(
+                    module: ash::vk::ShaderModule,
+                    spec: Option<&'a ash::vk::SpecializationInfo<'a>>,
+                ) -> ash::vk::PipelineShaderStageCreateInfo<'a> {
+                    let info = ash::vk::PipelineShaderStageCreateInfo::default()
+                        .stage(STAGE)
+                        .module(module)
+                        .name(ENTRY_POINT);
+
+                    match spec {
+                        Some(spec) => info.specialization_info(spec),
+                        None => info,
+                    }
+                }
+            }
+        });
 
         let dispatch = &self.dispatch;
+        let dispatch_commands = match (&self.dispatch, self.execution_model) {
+            (Some(dispatch), ExecutionModel::GLCompute) => Some(dispatch.command_tokens()),
+            _ => None,
+        };
+
+        let compute_pipeline = (self.execution_model == ExecutionModel::GLCompute)
+            .then(|| self.compute_pipeline_tokens());
+
+        let shader_object = self.shader_object_tokens();
+
+        let mesh_shading = self
+            .mesh_shading
+            .as_ref()
+            .map(|mesh_shading| mesh_shading.to_tokens(self.dispatch.as_ref()));
+
+        let geometry = &self.geometry;
+        let tessellation = &self.tessellation;
+        let fragment = &self.fragment;
+        let ray_tracing = &self.ray_tracing;
+        let kernel = &self.kernel;
 
         let vertex_inputs = &self.vertex_inputs;
 
         let new_tokens = quote! {
             pub mod #module_name {
                 pub const ENTRY_POINT: &core::ffi::CStr = #name_cstr;
-                pub const STAGE: ash::vk::ShaderStageFlags = #stage_tokens;
+                #stage
                 #dispatch
+                #dispatch_commands
+                #compute_pipeline
+                #shader_object
+                #mesh_shading
+                #geometry
+                #tessellation
+                #fragment
+                #ray_tracing
+                #kernel
                 #vertex_inputs
             }
         };
@@ -84,3 +306,111 @@ impl ToTokens for EntryPoint {
         tokens.extend(new_tokens);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use rspirv::dr::{Builder, Operand};
+    use spirv::{Decoration, ExecutionMode, ExecutionModel};
+
+    use crate::{
+        components::{EntryPoints, FromSpirv},
+        utilities::{
+            called_names, find_fn, load_built_module, parameter_names, parse_items,
+            referenced_names, statement_calling,
+        },
+    };
+
+    fn compute_entry_points(specialized: bool) -> EntryPoints {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        builder.entry_point(ExecutionModel::GLCompute, main, "main", []);
+        builder.execution_mode(main, ExecutionMode::LocalSize, [64, 1, 1]);
+        if specialized {
+            let uint = builder.type_int(32, 0);
+            let constant = builder.spec_constant_bit32(uint, 1);
+            builder.decorate(constant, Decoration::SpecId, [Operand::LiteralBit32(0)]);
+        }
+        let spirv = load_built_module(builder);
+
+        EntryPoints::from_spirv(&spirv).unwrap()
+    }
+
+    #[test]
+    fn compute_pipeline_owns_its_module() {
+        let items = parse_items(compute_entry_points(false).to_token_stream());
+        let create = find_fn(&items, "compute_main::create_compute_pipeline").unwrap();
+
+        assert_eq!(parameter_names(&create), ["device", "cache", "layout"]);
+
+        // The module is destroyed after the pipelines are created, whether or not it succeeded.
+        let create_module = statement_calling(&create, "create_shader_module").unwrap();
+        let create_pipelines = statement_calling(&create, "create_compute_pipelines").unwrap();
+        let destroy_module = statement_calling(&create, "destroy_shader_module").unwrap();
+        assert!(create_module < create_pipelines && create_pipelines < destroy_module);
+        assert!(statement_calling(&create, "map_err").unwrap() > destroy_module);
+
+        let calls = called_names(&create);
+        assert_eq!(
+            calls
+                .iter()
+                .filter(|call| *call == "destroy_shader_module")
+                .count(),
+            1
+        );
+        assert!(calls.contains(&"stage_create_info".to_string()));
+    }
+
+    #[test]
+    fn specialized_compute_pipeline() {
+        let items = parse_items(compute_entry_points(true).to_token_stream());
+        let create = find_fn(&items, "compute_main::create_compute_pipeline").unwrap();
+
+        assert_eq!(
+            parameter_names(&create),
+            ["device", "cache", "layout", "spec"]
+        );
+        assert!(called_names(&create).contains(&"specialization_map".to_string()));
+    }
+
+    fn mesh_entry_points(with_task_shader: bool) -> EntryPoints {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        builder.entry_point(ExecutionModel::MeshEXT, main, "main", []);
+        if with_task_shader {
+            let task = builder.id();
+            builder.entry_point(ExecutionModel::TaskEXT, task, "main", []);
+        }
+        let spirv = load_built_module(builder);
+
+        EntryPoints::from_spirv(&spirv).unwrap()
+    }
+
+    #[test]
+    fn mesh_without_task_shader() {
+        let mut entry_points = mesh_entry_points(false);
+
+        // A task shader of another module does not make this module's mesh shader depend on it.
+        entry_points.link_stages(&[ExecutionModel::TaskEXT, ExecutionModel::Fragment]);
+
+        let mesh = &entry_points.entry_points[0];
+        assert_eq!(mesh.next_stages(), [ExecutionModel::Fragment]);
+
+        let items = parse_items(entry_points.to_token_stream());
+        let create_info = find_fn(&items, "mesh_ext_main::shader_create_info").unwrap();
+        assert_eq!(parameter_names(&create_info), ["set_layouts", "spec"]);
+
+        let referenced = referenced_names(&create_info);
+        assert!(referenced.contains(&"BYTES".to_string()));
+        assert!(referenced.contains(&"NO_TASK_SHADER".to_string()));
+    }
+
+    #[test]
+    fn mesh_with_task_shader() {
+        let entry_points = mesh_entry_points(true);
+
+        let items = parse_items(entry_points.to_token_stream());
+        let create_info = find_fn(&items, "mesh_ext_main::shader_create_info").unwrap();
+        assert!(!referenced_names(&create_info).contains(&"NO_TASK_SHADER".to_string()));
+    }
+}
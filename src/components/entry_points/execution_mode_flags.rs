@@ -0,0 +1,41 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, format_ident, quote};
+use rspirv::dr::Module;
+use spirv::ExecutionMode;
+
+use crate::utilities::has_execution_mode;
+
+/// Execution modes without operands, emitted as a `bool` constant each.
+pub struct ExecutionModeFlags {
+    pub flags: Vec<(&'static str, bool)>,
+}
+
+impl ExecutionModeFlags {
+    pub fn for_entrypoint(
+        entry_point_id: u32,
+        modes: &[(&'static str, ExecutionMode)],
+        spirv: &Module,
+    ) -> Self {
+        let flags = modes
+            .iter()
+            .map(|(name, mode)| (*name, has_execution_mode(entry_point_id, *mode, spirv)))
+            .collect();
+
+        Self { flags }
+    }
+}
+
+impl ToTokens for ExecutionModeFlags {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let flags = self.flags.iter().map(|(name, value)| {
+            let name = format_ident!("{}", name);
+            quote! {pub const #name: bool = #value;}
+        });
+
+        let new_tokens = quote! {
+            #( #flags )*
+        };
+
+        tokens.extend(new_tokens);
+    }
+}
@@ -0,0 +1,462 @@
+use itertools::Itertools;
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use rspirv::dr::{Instruction, Module};
+use spirv::{Decoration, ExecutionMode, ExecutionModel, Op, StorageClass};
+
+use crate::{
+    types::{FromInstruction, Scalar, Type},
+    utilities::{find_decoration, find_instruction_with_id, has_decoration},
+};
+
+use super::execution_mode_flags::ExecutionModeFlags;
+
+const FRAGMENT_MODES: [(&str, ExecutionMode); 9] = [
+    ("EARLY_FRAGMENT_TESTS", ExecutionMode::EarlyFragmentTests),
+    ("DEPTH_REPLACING", ExecutionMode::DepthReplacing),
+    ("DEPTH_GREATER", ExecutionMode::DepthGreater),
+    ("DEPTH_LESS", ExecutionMode::DepthLess),
+    ("DEPTH_UNCHANGED", ExecutionMode::DepthUnchanged),
+    (
+        "PIXEL_INTERLOCK_ORDERED",
+        ExecutionMode::PixelInterlockOrderedEXT,
+    ),
+    (
+        "PIXEL_INTERLOCK_UNORDERED",
+        ExecutionMode::PixelInterlockUnorderedEXT,
+    ),
+    (
+        "SAMPLE_INTERLOCK_ORDERED",
+        ExecutionMode::SampleInterlockOrderedEXT,
+    ),
+    (
+        "SAMPLE_INTERLOCK_UNORDERED",
+        ExecutionMode::SampleInterlockUnorderedEXT,
+    ),
+];
+
+/// A fragment `Output` variable written to a color attachment.
+#[derive(Debug, Clone)]
+pub struct ColorOutput {
+    pub location: u32,
+    pub index: u32,
+    pub component_type: Scalar,
+}
+
+impl ColorOutput {
+    /// Resolves the outputs for a variable, arrays occupy consecutive locations.
+    pub fn from_variable(instruction: &Instruction, spirv: &Module) -> Vec<Self> {
+        // OpVariable | Result Type: <id> | Result <id> | Storage Class
+
+        if !matches!(instruction.class.opcode, Op::Variable) {
+            return Vec::new();
+        }
+
+        if instruction.operands[0].unwrap_storage_class() != StorageClass::Output {
+            return Vec::new();
+        }
+
+        let Some(result_id) = instruction.result_id else {
+            return Vec::new();
+        };
+
+        if has_decoration(result_id, Decoration::BuiltIn, spirv) {
+            return Vec::new();
+        }
+
+        let Some(location) = find_decoration(result_id, Decoration::Location, spirv)
+            .map(|operands| operands[0].unwrap_literal_bit32())
+        else {
+            return Vec::new();
+        };
+
+        let index = find_decoration(result_id, Decoration::Index, spirv)
+            .map(|operands| operands[0].unwrap_literal_bit32())
+            .unwrap_or(0);
+
+        let (component_type, location_count) = match Type::from_instruction(instruction, spirv) {
+            Some(Type::Scalar(scalar)) => (scalar, 1),
+            Some(Type::Vector(vector)) => (vector.component_type, 1),
+            Some(Type::Array(array)) => match *array.element_type {
+                Type::Scalar(scalar) => (scalar, array.length),
+                Type::Vector(vector) => (vector.component_type, array.length),
+                _ => return Vec::new(),
+            },
+            _ => return Vec::new(),
+        };
+
+        (0..location_count)
+            .map(|offset| Self {
+                location: location + offset,
+                index,
+                component_type: component_type.clone(),
+            })
+            .collect()
+    }
+}
+
+/// The execution modes and color outputs of a fragment entry point.
+pub struct Fragment {
+    pub flags: ExecutionModeFlags,
+    pub outputs: Vec<ColorOutput>,
+}
+
+impl FromInstruction for Fragment {
+    fn from_instruction(instruction: &Instruction, spirv: &Module) -> Option<Self> {
+        // OpEntryPoint | Execution Model | Entry Point: <id> | Name: Literal | <id>...
+
+        if !matches!(instruction.class.opcode, Op::EntryPoint) {
+            return None;
+        }
+
+        if instruction.operands[0].unwrap_execution_model() != ExecutionModel::Fragment {
+            return None;
+        }
+
+        let entry_point_id = instruction.operands[1].unwrap_id_ref();
+
+        let flags = ExecutionModeFlags::for_entrypoint(entry_point_id, &FRAGMENT_MODES, spirv);
+
+        let outputs = instruction.operands[3..]
+            .iter()
+            .filter_map(|operand| find_instruction_with_id(operand.unwrap_id_ref(), spirv))
+            .flat_map(|variable| ColorOutput::from_variable(variable, spirv))
+            .sorted_by_key(|output| (output.location, output.index))
+            .collect();
+
+        Some(Self { flags, outputs })
+    }
+}
+
+impl Fragment {
+    pub fn color_attachment_count(&self) -> u32 {
+        self.outputs
+            .iter()
+            .filter(|output| output.index == 0)
+            .map(|output| output.location + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn dual_source_blending(&self) -> bool {
+        self.outputs.iter().any(|output| output.index == 1)
+    }
+}
+
+impl ToTokens for Fragment {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let flags = &self.flags;
+
+        let color_attachment_count = self.color_attachment_count();
+        let attachment_count = color_attachment_count as usize;
+        let dual_source_blending = self.dual_source_blending();
+
+        let component_types = (0..color_attachment_count).map(|location| {
+            let output = self
+                .outputs
+                .iter()
+                .find(|output| output.location == location && output.index == 0);
+
+            match output {
+                Some(output) => {
+                    let component_type = output.component_type.to_component_type_tokens();
+                    quote! {Some(#component_type)}
+                }
+                None => quote! {None},
+            }
+        });
+
+        let new_tokens = quote! {
+            #flags
+
+            pub const COLOR_ATTACHMENT_COUNT: u32 = #color_attachment_count;
+            pub const DUAL_SOURCE_BLENDING: bool = #dual_source_blending;
+
+            pub const COLOR_ATTACHMENT_COMPONENT_TYPES: [Option<ash::vk::ComponentTypeKHR>; #attachment_count] = [
+                #( #component_types ),*
+            ];
+
+            pub fn color_blend_attachment_states() -> [ash::vk::PipelineColorBlendAttachmentState; #attachment_count] {
+                [
+                    ash::vk::PipelineColorBlendAttachmentState::default()
+                        .color_write_mask(ash::vk::ColorComponentFlags::RGBA);
+                    #attachment_count
+                ]
+            }
+
+            pub fn is_color_attachment_format_compatible(location: u32, format: ash::vk::Format) -> bool {
+                let Some(Some(component_type)) = COLOR_ATTACHMENT_COMPONENT_TYPES.get(location as usize) else {
+                    return true;
+                };
+
+                if format == ash::vk::Format::UNDEFINED {
+                    return true;
+                }
+
+                // Formats grouped by the numeric type of their components, depth/stencil, compressed
+                // and multi-planar formats cannot be color attachments.
+                let component_types: &[ash::vk::ComponentTypeKHR] = match format {
+                    ash::vk::Format::R8_SINT
+                    | ash::vk::Format::R8G8_SINT
+                    | ash::vk::Format::R8G8B8_SINT
+                    | ash::vk::Format::B8G8R8_SINT
+                    | ash::vk::Format::R8G8B8A8_SINT
+                    | ash::vk::Format::B8G8R8A8_SINT
+                    | ash::vk::Format::A8B8G8R8_SINT_PACK32
+                    | ash::vk::Format::A2R10G10B10_SINT_PACK32
+                    | ash::vk::Format::A2B10G10R10_SINT_PACK32
+                    | ash::vk::Format::R16_SINT
+                    | ash::vk::Format::R16G16_SINT
+                    | ash::vk::Format::R16G16B16_SINT
+                    | ash::vk::Format::R16G16B16A16_SINT
+                    | ash::vk::Format::R32_SINT
+                    | ash::vk::Format::R32G32_SINT
+                    | ash::vk::Format::R32G32B32_SINT
+                    | ash::vk::Format::R32G32B32A32_SINT
+                    | ash::vk::Format::R64_SINT
+                    | ash::vk::Format::R64G64_SINT
+                    | ash::vk::Format::R64G64B64_SINT
+                    | ash::vk::Format::R64G64B64A64_SINT => &[
+                        ash::vk::ComponentTypeKHR::SINT8,
+                        ash::vk::ComponentTypeKHR::SINT16,
+                        ash::vk::ComponentTypeKHR::SINT32,
+                        ash::vk::ComponentTypeKHR::SINT64,
+                    ],
+
+                    ash::vk::Format::R8_UINT
+                    | ash::vk::Format::R8G8_UINT
+                    | ash::vk::Format::R8G8B8_UINT
+                    | ash::vk::Format::B8G8R8_UINT
+                    | ash::vk::Format::R8G8B8A8_UINT
+                    | ash::vk::Format::B8G8R8A8_UINT
+                    | ash::vk::Format::A8B8G8R8_UINT_PACK32
+                    | ash::vk::Format::A2R10G10B10_UINT_PACK32
+                    | ash::vk::Format::A2B10G10R10_UINT_PACK32
+                    | ash::vk::Format::R16_UINT
+                    | ash::vk::Format::R16G16_UINT
+                    | ash::vk::Format::R16G16B16_UINT
+                    | ash::vk::Format::R16G16B16A16_UINT
+                    | ash::vk::Format::R32_UINT
+                    | ash::vk::Format::R32G32_UINT
+                    | ash::vk::Format::R32G32B32_UINT
+                    | ash::vk::Format::R32G32B32A32_UINT
+                    | ash::vk::Format::R64_UINT
+                    | ash::vk::Format::R64G64_UINT
+                    | ash::vk::Format::R64G64B64_UINT
+                    | ash::vk::Format::R64G64B64A64_UINT => &[
+                        ash::vk::ComponentTypeKHR::UINT8,
+                        ash::vk::ComponentTypeKHR::UINT16,
+                        ash::vk::ComponentTypeKHR::UINT32,
+                        ash::vk::ComponentTypeKHR::UINT64,
+                    ],
+
+                    ash::vk::Format::R4G4_UNORM_PACK8
+                    | ash::vk::Format::R4G4B4A4_UNORM_PACK16
+                    | ash::vk::Format::B4G4R4A4_UNORM_PACK16
+                    | ash::vk::Format::A4R4G4B4_UNORM_PACK16
+                    | ash::vk::Format::A4B4G4R4_UNORM_PACK16
+                    | ash::vk::Format::R5G6B5_UNORM_PACK16
+                    | ash::vk::Format::B5G6R5_UNORM_PACK16
+                    | ash::vk::Format::R5G5B5A1_UNORM_PACK16
+                    | ash::vk::Format::B5G5R5A1_UNORM_PACK16
+                    | ash::vk::Format::A1R5G5B5_UNORM_PACK16
+                    | ash::vk::Format::A1B5G5R5_UNORM_PACK16_KHR
+                    | ash::vk::Format::A8_UNORM_KHR
+                    | ash::vk::Format::R8_UNORM
+                    | ash::vk::Format::R8_SNORM
+                    | ash::vk::Format::R8_USCALED
+                    | ash::vk::Format::R8_SSCALED
+                    | ash::vk::Format::R8_SRGB
+                    | ash::vk::Format::R8G8_UNORM
+                    | ash::vk::Format::R8G8_SNORM
+                    | ash::vk::Format::R8G8_USCALED
+                    | ash::vk::Format::R8G8_SSCALED
+                    | ash::vk::Format::R8G8_SRGB
+                    | ash::vk::Format::R8G8B8_UNORM
+                    | ash::vk::Format::R8G8B8_SNORM
+                    | ash::vk::Format::R8G8B8_USCALED
+                    | ash::vk::Format::R8G8B8_SSCALED
+                    | ash::vk::Format::R8G8B8_SRGB
+                    | ash::vk::Format::B8G8R8_UNORM
+                    | ash::vk::Format::B8G8R8_SNORM
+                    | ash::vk::Format::B8G8R8_USCALED
+                    | ash::vk::Format::B8G8R8_SSCALED
+                    | ash::vk::Format::B8G8R8_SRGB
+                    | ash::vk::Format::R8G8B8A8_UNORM
+                    | ash::vk::Format::R8G8B8A8_SNORM
+                    | ash::vk::Format::R8G8B8A8_USCALED
+                    | ash::vk::Format::R8G8B8A8_SSCALED
+                    | ash::vk::Format::R8G8B8A8_SRGB
+                    | ash::vk::Format::B8G8R8A8_UNORM
+                    | ash::vk::Format::B8G8R8A8_SNORM
+                    | ash::vk::Format::B8G8R8A8_USCALED
+                    | ash::vk::Format::B8G8R8A8_SSCALED
+                    | ash::vk::Format::B8G8R8A8_SRGB
+                    | ash::vk::Format::A8B8G8R8_UNORM_PACK32
+                    | ash::vk::Format::A8B8G8R8_SNORM_PACK32
+                    | ash::vk::Format::A8B8G8R8_USCALED_PACK32
+                    | ash::vk::Format::A8B8G8R8_SSCALED_PACK32
+                    | ash::vk::Format::A8B8G8R8_SRGB_PACK32
+                    | ash::vk::Format::A2R10G10B10_UNORM_PACK32
+                    | ash::vk::Format::A2R10G10B10_SNORM_PACK32
+                    | ash::vk::Format::A2R10G10B10_USCALED_PACK32
+                    | ash::vk::Format::A2R10G10B10_SSCALED_PACK32
+                    | ash::vk::Format::A2B10G10R10_UNORM_PACK32
+                    | ash::vk::Format::A2B10G10R10_SNORM_PACK32
+                    | ash::vk::Format::A2B10G10R10_USCALED_PACK32
+                    | ash::vk::Format::A2B10G10R10_SSCALED_PACK32
+                    | ash::vk::Format::R16_UNORM
+                    | ash::vk::Format::R16_SNORM
+                    | ash::vk::Format::R16_USCALED
+                    | ash::vk::Format::R16_SSCALED
+                    | ash::vk::Format::R16_SFLOAT
+                    | ash::vk::Format::R16G16_UNORM
+                    | ash::vk::Format::R16G16_SNORM
+                    | ash::vk::Format::R16G16_USCALED
+                    | ash::vk::Format::R16G16_SSCALED
+                    | ash::vk::Format::R16G16_SFLOAT
+                    | ash::vk::Format::R16G16B16_UNORM
+                    | ash::vk::Format::R16G16B16_SNORM
+                    | ash::vk::Format::R16G16B16_USCALED
+                    | ash::vk::Format::R16G16B16_SSCALED
+                    | ash::vk::Format::R16G16B16_SFLOAT
+                    | ash::vk::Format::R16G16B16A16_UNORM
+                    | ash::vk::Format::R16G16B16A16_SNORM
+                    | ash::vk::Format::R16G16B16A16_USCALED
+                    | ash::vk::Format::R16G16B16A16_SSCALED
+                    | ash::vk::Format::R16G16B16A16_SFLOAT
+                    | ash::vk::Format::R32_SFLOAT
+                    | ash::vk::Format::R32G32_SFLOAT
+                    | ash::vk::Format::R32G32B32_SFLOAT
+                    | ash::vk::Format::R32G32B32A32_SFLOAT
+                    | ash::vk::Format::R64_SFLOAT
+                    | ash::vk::Format::R64G64_SFLOAT
+                    | ash::vk::Format::R64G64B64_SFLOAT
+                    | ash::vk::Format::R64G64B64A64_SFLOAT
+                    | ash::vk::Format::B10G11R11_UFLOAT_PACK32
+                    | ash::vk::Format::E5B9G9R9_UFLOAT_PACK32
+                    | ash::vk::Format::R16G16_S10_5_NV => &[
+                        ash::vk::ComponentTypeKHR::FLOAT16,
+                        ash::vk::ComponentTypeKHR::FLOAT32,
+                        ash::vk::ComponentTypeKHR::FLOAT64,
+                    ],
+
+                    _ => &[],
+                };
+
+                component_types.contains(component_type)
+            }
+
+            pub const fn color_blend_state_compatible(attachment_count: u32) -> bool {
+                attachment_count >= COLOR_ATTACHMENT_COUNT
+            }
+
+            pub fn color_attachment_formats_compatible(formats: &[ash::vk::Format]) -> bool {
+                color_blend_state_compatible(formats.len() as u32)
+                    && formats
+                        .iter()
+                        .enumerate()
+                        .all(|(location, format)| is_color_attachment_format_compatible(location as u32, *format))
+            }
+        };
+
+        tokens.extend(new_tokens);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use rspirv::dr::{Builder, Operand};
+    use spirv::{Decoration, ExecutionModel, StorageClass};
+
+    use crate::{
+        types::{FromInstruction, Scalar},
+        utilities::{find_const, find_fn, load_built_module, match_arms, parse_items},
+    };
+
+    use super::Fragment;
+
+    fn output(builder: &mut Builder, pointee: u32, location: u32) -> u32 {
+        let pointer = builder.type_pointer(None, StorageClass::Output, pointee);
+        let variable = builder.variable(pointer, None, StorageClass::Output, None);
+        builder.decorate(
+            variable,
+            Decoration::Location,
+            [Operand::LiteralBit32(location)],
+        );
+        variable
+    }
+
+    fn fragment() -> Fragment {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        let float = builder.type_float(32);
+        let uint = builder.type_int(32, 0);
+        let vec4 = builder.type_vector(float, 4);
+        let uvec4 = builder.type_vector(uint, 4);
+        let color = output(&mut builder, vec4, 0);
+        let id = output(&mut builder, uvec4, 2);
+        builder.entry_point(ExecutionModel::Fragment, main, "main", [color, id]);
+        let spirv = load_built_module(builder);
+
+        Fragment::from_instruction(&spirv.entry_points[0], &spirv).unwrap()
+    }
+
+    #[test]
+    fn color_attachments() {
+        let fragment = fragment();
+
+        let [color, id] = fragment.outputs.as_slice() else {
+            panic!("expected two color outputs");
+        };
+        assert_eq!((color.location, id.location), (0, 2));
+        assert!(matches!(color.component_type, Scalar::F32));
+        assert!(matches!(id.component_type, Scalar::U32));
+        assert_eq!(fragment.color_attachment_count(), 3);
+        assert!(!fragment.dual_source_blending());
+
+        let items = parse_items(fragment.to_token_stream());
+        assert_eq!(
+            find_const(&items, "COLOR_ATTACHMENT_COMPONENT_TYPES"),
+            Some(syn::parse_quote! {[
+                Some(ash::vk::ComponentTypeKHR::FLOAT32),
+                None,
+                Some(ash::vk::ComponentTypeKHR::UINT32)
+            ]})
+        );
+    }
+
+    #[test]
+    fn format_classes() {
+        let items = parse_items(fragment().to_token_stream());
+        let compatible = find_fn(&items, "is_color_attachment_format_compatible").unwrap();
+
+        let arms = match_arms(&compatible);
+        let [(sint, _), (uint, _), (float, _), (rest, _)] = arms.as_slice() else {
+            panic!("expected a match arm per numeric type and a fallback");
+        };
+
+        assert!(sint.iter().all(|format| format.contains("_SINT")));
+        assert!(uint.iter().all(|format| format.contains("_UINT")));
+        assert!(
+            float
+                .iter()
+                .all(|format| !format.contains("_SINT") && !format.contains("_UINT"))
+        );
+        assert!(rest.is_empty());
+
+        // Every integer format has a counterpart of the other signedness.
+        let unsigned: Vec<_> = sint
+            .iter()
+            .map(|format| format.replace("_SINT", "_UINT"))
+            .collect();
+        assert_eq!(&unsigned, uint);
+
+        for components in ["R64", "R64G64", "R64G64B64", "R64G64B64A64"] {
+            assert!(sint.contains(&format!("{components}_SINT")));
+            assert!(uint.contains(&format!("{components}_UINT")));
+            assert!(float.contains(&format!("{components}_SFLOAT")));
+        }
+    }
+}
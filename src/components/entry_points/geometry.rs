@@ -0,0 +1,171 @@
+use quote::{ToTokens, quote};
+use rspirv::dr::{Instruction, Module};
+use spirv::{ExecutionMode, ExecutionModel, Op};
+
+use crate::{
+    types::{FromInstruction, TypeSyntax},
+    utilities::{find_execution_mode, has_execution_mode},
+};
+
+use super::topology::Topology;
+
+/// The execution modes of a geometry entry point.
+pub struct Geometry {
+    pub invocations: u32,
+    pub output_vertices: Option<u32>,
+    pub input_topology: Option<Topology>,
+    pub output_topology: Option<Topology>,
+}
+
+impl FromInstruction for Geometry {
+    fn from_instruction(instruction: &Instruction, spirv: &Module) -> Option<Self> {
+        // OpEntryPoint | Execution Model | Entry Point: <id> | Name: Literal | <id>...
+
+        if !matches!(instruction.class.opcode, Op::EntryPoint) {
+            return None;
+        }
+
+        if instruction.operands[0].unwrap_execution_model() != ExecutionModel::Geometry {
+            return None;
+        }
+
+        let entry_point_id = instruction.operands[1].unwrap_id_ref();
+
+        // Geometry shaders are invoked once per primitive unless specified otherwise.
+        let invocations = find_execution_mode(entry_point_id, ExecutionMode::Invocations, spirv)
+            .map(|operands| operands[0].unwrap_literal_bit32())
+            .unwrap_or(1);
+
+        let output_vertices =
+            find_execution_mode(entry_point_id, ExecutionMode::OutputVertices, spirv)
+                .map(|operands| operands[0].unwrap_literal_bit32());
+
+        let input_topology = [
+            (ExecutionMode::InputPoints, Topology::PointList),
+            (ExecutionMode::InputLines, Topology::LineList),
+            (
+                ExecutionMode::InputLinesAdjacency,
+                Topology::LineListWithAdjacency,
+            ),
+            (ExecutionMode::Triangles, Topology::TriangleList),
+            (
+                ExecutionMode::InputTrianglesAdjacency,
+                Topology::TriangleListWithAdjacency,
+            ),
+        ]
+        .into_iter()
+        .find_map(|(mode, topology)| {
+            has_execution_mode(entry_point_id, mode, spirv).then_some(topology)
+        });
+
+        let output_topology = [
+            (ExecutionMode::OutputPoints, Topology::PointList),
+            (ExecutionMode::OutputLineStrip, Topology::LineStrip),
+            (ExecutionMode::OutputTriangleStrip, Topology::TriangleStrip),
+        ]
+        .into_iter()
+        .find_map(|(mode, topology)| {
+            has_execution_mode(entry_point_id, mode, spirv).then_some(topology)
+        });
+
+        Some(Self {
+            invocations,
+            output_vertices,
+            input_topology,
+            output_topology,
+        })
+    }
+}
+
+impl ToTokens for Geometry {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let invocations = self.invocations;
+
+        let output_vertices = self.output_vertices.map(|output_vertices| {
+            quote! {pub const OUTPUT_VERTICES: u32 = #output_vertices;}
+        });
+
+        let input_topology = self.input_topology.map(|topology| {
+            let topology = topology.to_type_syntax();
+
+            quote! {
+                pub const INPUT_TOPOLOGY: ash::vk::PrimitiveTopology = #topology;
+
+                pub fn input_assembly_state_create_info<'a>() -> ash::vk::PipelineInputAssemblyStateCreateInfo<'a> {
+                    ash::vk::PipelineInputAssemblyStateCreateInfo::default()
+                        .topology(INPUT_TOPOLOGY)
+                }
+            }
+        });
+
+        let output_topology = self.output_topology.map(|topology| {
+            let topology = topology.to_type_syntax();
+            quote! {pub const OUTPUT_TOPOLOGY: ash::vk::PrimitiveTopology = #topology;}
+        });
+
+        let new_tokens = quote! {
+            pub const INVOCATIONS: u32 = #invocations;
+            #output_vertices
+            #input_topology
+            #output_topology
+        };
+
+        tokens.extend(new_tokens);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use rspirv::dr::Builder;
+    use spirv::{ExecutionMode, ExecutionModel};
+
+    use crate::{
+        components::entry_points::topology::Topology,
+        types::FromInstruction,
+        utilities::{find_const, load_built_module, parse_items},
+    };
+
+    use super::Geometry;
+
+    #[test]
+    fn topologies_and_invocations() {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        builder.entry_point(ExecutionModel::Geometry, main, "main", []);
+        builder.execution_mode(main, ExecutionMode::InputLinesAdjacency, []);
+        builder.execution_mode(main, ExecutionMode::OutputTriangleStrip, []);
+        builder.execution_mode(main, ExecutionMode::OutputVertices, [4]);
+        builder.execution_mode(main, ExecutionMode::Invocations, [2]);
+        let spirv = load_built_module(builder);
+
+        let geometry = Geometry::from_instruction(&spirv.entry_points[0], &spirv).unwrap();
+
+        assert_eq!(geometry.invocations, 2);
+        assert_eq!(geometry.output_vertices, Some(4));
+        assert_eq!(
+            geometry.input_topology,
+            Some(Topology::LineListWithAdjacency)
+        );
+        assert_eq!(geometry.output_topology, Some(Topology::TriangleStrip));
+
+        let items = parse_items(geometry.to_token_stream());
+        assert_eq!(
+            find_const(&items, "INPUT_TOPOLOGY"),
+            Some(syn::parse_quote! {ash::vk::PrimitiveTopology::LINE_LIST_WITH_ADJACENCY})
+        );
+    }
+
+    #[test]
+    fn single_invocation_by_default() {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        builder.entry_point(ExecutionModel::Geometry, main, "main", []);
+        let spirv = load_built_module(builder);
+
+        let geometry = Geometry::from_instruction(&spirv.entry_points[0], &spirv).unwrap();
+
+        assert_eq!(geometry.invocations, 1);
+        assert_eq!(geometry.input_topology, None);
+    }
+}
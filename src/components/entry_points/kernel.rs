@@ -0,0 +1,207 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use rspirv::dr::{Instruction, Module};
+use spirv::{ExecutionMode, ExecutionModel, Op, StorageClass};
+use tracing::warn;
+
+use crate::{
+    types::{FromInstruction, Scalar, Structure, Type},
+    utilities::{find_execution_mode, find_instruction_with_id, find_name_for_id},
+};
+
+/// The arguments and execution modes of an OpenCL kernel entry point.
+pub struct Kernel {
+    pub arguments: Option<Structure>,
+    pub argument_count: u32,
+    pub local_size_hint: Option<[u32; 3]>,
+}
+
+impl Kernel {
+    /// Resolves the field for a kernel argument. Global buffers are passed by their device
+    /// address and local buffers by their size in bytes, as they are allocated by the runtime.
+    fn argument_field(
+        parameter: &Instruction,
+        name: String,
+        spirv: &Module,
+    ) -> Option<(Type, String)> {
+        // OpFunctionParameter | Result Type: <id> | Result <id>
+
+        let type_instruction = find_instruction_with_id(parameter.result_type?, spirv)?;
+
+        if !matches!(type_instruction.class.opcode, Op::TypePointer) {
+            return Some((Type::from_instruction(type_instruction, spirv)?, name));
+        }
+
+        // OpTypePointer | Result <id> | Storage Class | Type: <id>
+        match type_instruction.operands[0].unwrap_storage_class() {
+            StorageClass::CrossWorkgroup | StorageClass::Generic => {
+                Some((Type::Scalar(Scalar::U64), name))
+            }
+            StorageClass::Workgroup => Some((Type::Scalar(Scalar::U64), format!("{name}_size"))),
+            storage_class => {
+                warn!("Unsupported storage class for kernel argument {name}: {storage_class:?}");
+                None
+            }
+        }
+    }
+}
+
+impl FromInstruction for Kernel {
+    fn from_instruction(instruction: &Instruction, spirv: &Module) -> Option<Self> {
+        // OpEntryPoint | Execution Model | Entry Point: <id> | Name: Literal | <id>...
+
+        if !matches!(instruction.class.opcode, Op::EntryPoint) {
+            return None;
+        }
+
+        if instruction.operands[0].unwrap_execution_model() != ExecutionModel::Kernel {
+            return None;
+        }
+
+        let entry_point_id = instruction.operands[1].unwrap_id_ref();
+
+        let parameters = spirv
+            .functions
+            .iter()
+            .find(|function| {
+                function
+                    .def
+                    .as_ref()
+                    .is_some_and(|def| def.result_id == Some(entry_point_id))
+            })
+            .map(|function| function.parameters.as_slice())
+            .unwrap_or_default();
+
+        // Opaque arguments such as images and samplers have no plain data representation,
+        // so the argument structure is only emitted when every argument can be represented.
+        let arguments = parameters
+            .iter()
+            .enumerate()
+            .map(|(index, parameter)| {
+                let argument_name = parameter
+                    .result_id
+                    .and_then(|id| find_name_for_id(id, spirv))
+                    .unwrap_or_else(|| format!("argument_{}", index));
+
+                Self::argument_field(parameter, argument_name, spirv)
+            })
+            .collect::<Option<Vec<_>>>()
+            .filter(|fields| !fields.is_empty())
+            .map(|fields| Structure::from_fields(fields, "Arguments".to_string()));
+
+        let local_size_hint =
+            find_execution_mode(entry_point_id, ExecutionMode::LocalSizeHint, spirv).map(
+                |operands| {
+                    [
+                        operands[0].unwrap_literal_bit32(),
+                        operands[1].unwrap_literal_bit32(),
+                        operands[2].unwrap_literal_bit32(),
+                    ]
+                },
+            );
+
+        Some(Self {
+            arguments,
+            argument_count: parameters.len() as u32,
+            local_size_hint,
+        })
+    }
+}
+
+impl ToTokens for Kernel {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let argument_count = self.argument_count;
+        let arguments = &self.arguments;
+
+        let local_size_hint = self.local_size_hint.map(|[x, y, z]| {
+            quote! {pub const LOCAL_SIZE_HINT: [u32; 3] = [#x, #y, #z];}
+        });
+
+        let new_tokens = quote! {
+            pub const ARGUMENT_COUNT: u32 = #argument_count;
+            #arguments
+            #local_size_hint
+        };
+
+        tokens.extend(new_tokens);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rspirv::dr::Builder;
+    use spirv::{ExecutionModel, FunctionControl, StorageClass};
+
+    use crate::{
+        types::{FromInstruction, Scalar, Type},
+        utilities::load_built_module,
+    };
+
+    use super::Kernel;
+
+    fn kernel(storage_classes: &[StorageClass]) -> Kernel {
+        let mut builder = Builder::new();
+        let void = builder.type_void();
+        let uint = builder.type_int(32, 0);
+        let parameter_types: Vec<_> = storage_classes
+            .iter()
+            .map(|storage_class| builder.type_pointer(None, *storage_class, uint))
+            .chain([uint])
+            .collect();
+        let function_type = builder.type_function(void, parameter_types.clone());
+
+        let main = builder
+            .begin_function(void, None, FunctionControl::NONE, function_type)
+            .unwrap();
+        for (parameter_type, name) in parameter_types
+            .into_iter()
+            .zip(["data", "scratch", "count"])
+        {
+            let parameter = builder.function_parameter(parameter_type).unwrap();
+            builder.name(parameter, name);
+        }
+        builder.begin_block(None).unwrap();
+        builder.ret().unwrap();
+        builder.end_function().unwrap();
+
+        builder.entry_point(ExecutionModel::Kernel, main, "main", []);
+        let spirv = load_built_module(builder);
+
+        Kernel::from_instruction(&spirv.entry_points[0], &spirv).unwrap()
+    }
+
+    #[test]
+    fn pointer_arguments() {
+        let kernel = kernel(&[StorageClass::CrossWorkgroup, StorageClass::Workgroup]);
+
+        assert_eq!(kernel.argument_count, 3);
+
+        let arguments = kernel.arguments.unwrap();
+        let fields: Vec<_> = arguments
+            .members
+            .iter()
+            .map(|member| (member.name.as_str(), member.offset))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("data", 0),
+                ("scratch_size", 8),
+                ("count", 16),
+                ("_padding_0", 20)
+            ]
+        );
+        assert!(matches!(
+            *arguments.members[1].member_type,
+            Type::Scalar(Scalar::U64)
+        ));
+    }
+
+    #[test]
+    fn unsupported_pointer_arguments() {
+        let kernel = kernel(&[StorageClass::CrossWorkgroup, StorageClass::Function]);
+
+        assert_eq!(kernel.argument_count, 3);
+        assert!(kernel.arguments.is_none());
+    }
+}
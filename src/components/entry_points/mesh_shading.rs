@@ -0,0 +1,267 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use rspirv::dr::{Instruction, Module};
+use spirv::{ExecutionMode, ExecutionModel, Op, StorageClass};
+
+use crate::{
+    types::{FromInstruction, Structure, TypeSyntax},
+    utilities::{find_execution_mode, find_instruction_with_id, has_execution_mode},
+};
+
+use super::{dispatch::Dispatch, topology::Topology};
+
+/// The execution modes and payload of a task or mesh entry point.
+pub struct MeshShading {
+    pub execution_model: ExecutionModel,
+    pub max_vertices: Option<u32>,
+    pub max_primitives: Option<u32>,
+    pub output_topology: Option<Topology>,
+    pub payload: Option<Structure>,
+}
+
+impl FromInstruction for MeshShading {
+    fn from_instruction(instruction: &Instruction, spirv: &Module) -> Option<Self> {
+        // OpEntryPoint | Execution Model | Entry Point: <id> | Name: Literal | <id>...
+
+        if !matches!(instruction.class.opcode, Op::EntryPoint) {
+            return None;
+        }
+
+        let execution_model = instruction.operands[0].unwrap_execution_model();
+        if !matches!(
+            execution_model,
+            ExecutionModel::TaskEXT
+                | ExecutionModel::MeshEXT
+                | ExecutionModel::TaskNV
+                | ExecutionModel::MeshNV
+        ) {
+            return None;
+        }
+
+        let entry_point_id = instruction.operands[1].unwrap_id_ref();
+
+        let max_vertices =
+            find_execution_mode(entry_point_id, ExecutionMode::OutputVertices, spirv)
+                .map(|operands| operands[0].unwrap_literal_bit32());
+
+        let max_primitives =
+            find_execution_mode(entry_point_id, ExecutionMode::OutputPrimitivesEXT, spirv)
+                .map(|operands| operands[0].unwrap_literal_bit32());
+
+        let output_topology = [
+            (ExecutionMode::OutputPoints, Topology::PointList),
+            (ExecutionMode::OutputLinesEXT, Topology::LineList),
+            (ExecutionMode::OutputTrianglesEXT, Topology::TriangleList),
+        ]
+        .into_iter()
+        .find_map(|(mode, topology)| {
+            has_execution_mode(entry_point_id, mode, spirv).then_some(topology)
+        });
+
+        // Resolve the payload from the entry point's interface
+        let payload = instruction.operands[3..].iter().find_map(|operand| {
+            // OpVariable | Result Type: <id> | Result <id> | Storage Class
+
+            let variable = find_instruction_with_id(operand.unwrap_id_ref(), spirv)?;
+
+            if !matches!(variable.class.opcode, Op::Variable) {
+                return None;
+            }
+
+            if variable.operands[0].unwrap_storage_class() != StorageClass::TaskPayloadWorkgroupEXT
+            {
+                return None;
+            }
+
+            // OpTypePointer | Result <id> | Storage Class | Type: <id>
+            let pointer = find_instruction_with_id(variable.result_type?, spirv)?;
+            let pointee = find_instruction_with_id(pointer.operands[1].unwrap_id_ref(), spirv)?;
+
+            Structure::from_natural_layout(pointee, spirv)
+        });
+
+        Some(Self {
+            execution_model,
+            max_vertices,
+            max_primitives,
+            output_topology,
+            payload,
+        })
+    }
+}
+
+impl MeshShading {
+    pub fn to_tokens(&self, dispatch: Option<&Dispatch>) -> TokenStream {
+        let max_vertices = self.max_vertices.map(|max_vertices| {
+            quote! {pub const MAX_VERTICES: u32 = #max_vertices;}
+        });
+
+        let max_primitives = self.max_primitives.map(|max_primitives| {
+            quote! {pub const MAX_PRIMITIVES: u32 = #max_primitives;}
+        });
+
+        let output_topology = self.output_topology.map(|topology| {
+            let topology = topology.to_type_syntax();
+            quote! {pub const OUTPUT_TOPOLOGY: ash::vk::PrimitiveTopology = #topology;}
+        });
+
+        let payload = self.payload.as_ref().map(|structure| {
+            let size = structure.layout.size() as u32;
+
+            quote! {
+                #structure
+
+                pub const PAYLOAD_SIZE: u32 = #size;
+            }
+        });
+
+        let limits = self.limits_tokens(dispatch);
+
+        quote! {
+            #max_vertices
+            #max_primitives
+            #output_topology
+            #payload
+            #limits
+        }
+    }
+
+    fn limits_tokens(&self, dispatch: Option<&Dispatch>) -> Option<TokenStream> {
+        let is_task = match self.execution_model {
+            ExecutionModel::TaskEXT => true,
+            ExecutionModel::MeshEXT => false,
+
+            // The NV models are limited by PhysicalDeviceMeshShaderPropertiesNV instead.
+            _ => return None,
+        };
+
+        let mut checks = Vec::new();
+
+        if self.max_vertices.is_some() {
+            checks.push(quote! {MAX_VERTICES <= properties.max_mesh_output_vertices});
+        }
+
+        if self.max_primitives.is_some() {
+            checks.push(quote! {MAX_PRIMITIVES <= properties.max_mesh_output_primitives});
+        }
+
+        if self.payload.is_some() {
+            checks.push(quote! {PAYLOAD_SIZE <= properties.max_task_payload_size});
+        }
+
+        let (spec_parameter, size) = match dispatch {
+            Some(dispatch) => {
+                let (max_size, max_invocations) = if is_task {
+                    (
+                        quote! {properties.max_task_work_group_size},
+                        quote! {properties.max_task_work_group_invocations},
+                    )
+                } else {
+                    (
+                        quote! {properties.max_mesh_work_group_size},
+                        quote! {properties.max_mesh_work_group_invocations},
+                    )
+                };
+
+                checks.push(quote! {size[0] <= #max_size[0]});
+                checks.push(quote! {size[1] <= #max_size[1]});
+                checks.push(quote! {size[2] <= #max_size[2]});
+                checks.push(quote! {size[0] * size[1] * size[2] <= #max_invocations});
+
+                let (spec_parameter, _) = dispatch.spec_tokens();
+                let size = dispatch.size_tokens();
+
+                (spec_parameter, Some(quote! {let size = #size;}))
+            }
+
+            None => (TokenStream::new(), None),
+        };
+
+        if checks.is_empty() {
+            return None;
+        }
+
+        Some(quote! {
+            pub fn within_mesh_shader_limits(
+                #spec_parameter
+                properties: &ash::vk::PhysicalDeviceMeshShaderPropertiesEXT<'_>,
+            ) -> bool {
+                #size
+
+                #( #checks )&&*
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rspirv::dr::Builder;
+    use spirv::{ExecutionMode, ExecutionModel, StorageClass};
+
+    use crate::{
+        components::entry_points::{dispatch::Dispatch, topology::Topology},
+        types::FromInstruction,
+        utilities::{find_const, find_fn, load_built_module, parse_items, referenced_names},
+    };
+
+    use super::MeshShading;
+
+    #[test]
+    fn mesh_outputs() {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        builder.entry_point(ExecutionModel::MeshEXT, main, "main", []);
+        builder.execution_mode(main, ExecutionMode::LocalSize, [32, 1, 1]);
+        builder.execution_mode(main, ExecutionMode::OutputVertices, [64]);
+        builder.execution_mode(main, ExecutionMode::OutputPrimitivesEXT, [126]);
+        builder.execution_mode(main, ExecutionMode::OutputTrianglesEXT, []);
+        let spirv = load_built_module(builder);
+
+        let mesh_shading = MeshShading::from_instruction(&spirv.entry_points[0], &spirv).unwrap();
+
+        assert_eq!(mesh_shading.max_vertices, Some(64));
+        assert_eq!(mesh_shading.max_primitives, Some(126));
+        assert_eq!(mesh_shading.output_topology, Some(Topology::TriangleList));
+        assert!(mesh_shading.payload.is_none());
+
+        let dispatch = Dispatch::for_entrypoint(main, ExecutionModel::MeshEXT, &spirv);
+        let items = parse_items(mesh_shading.to_tokens(dispatch.as_ref()));
+
+        let limits = find_fn(&items, "within_mesh_shader_limits").unwrap();
+        let referenced = referenced_names(&limits);
+        assert!(referenced.contains(&"MAX_VERTICES".to_string()));
+        assert!(referenced.contains(&"MAX_PRIMITIVES".to_string()));
+        assert!(referenced.contains(&"DISPATCH_SIZE".to_string()));
+        assert!(!referenced.contains(&"PAYLOAD_SIZE".to_string()));
+    }
+
+    #[test]
+    fn task_payload() {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        let uint = builder.type_int(32, 0);
+        let uvec3 = builder.type_vector(uint, 3);
+        let payload = builder.type_struct([uvec3, uint]);
+        builder.name(payload, "TaskPayload");
+        let pointer = builder.type_pointer(None, StorageClass::TaskPayloadWorkgroupEXT, payload);
+        let variable = builder.variable(pointer, None, StorageClass::TaskPayloadWorkgroupEXT, None);
+        builder.entry_point(ExecutionModel::TaskEXT, main, "main", [variable]);
+        let spirv = load_built_module(builder);
+
+        let mesh_shading = MeshShading::from_instruction(&spirv.entry_points[0], &spirv).unwrap();
+
+        // The payload has no explicit layout, so it is laid out naturally.
+        let payload = mesh_shading.payload.as_ref().unwrap();
+        assert_eq!(payload.name, "TaskPayload");
+        assert_eq!(payload.layout.size(), 16);
+
+        let items = parse_items(mesh_shading.to_tokens(None));
+        assert_eq!(
+            find_const(&items, "PAYLOAD_SIZE"),
+            Some(syn::parse_quote! {16u32})
+        );
+        let limits = find_fn(&items, "within_mesh_shader_limits").unwrap();
+        assert!(referenced_names(&limits).contains(&"PAYLOAD_SIZE".to_string()));
+    }
+}
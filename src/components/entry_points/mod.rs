@@ -1,10 +1,22 @@
 mod dispatch;
 mod entry_point;
+mod execution_mode_flags;
+mod fragment;
+mod geometry;
+mod kernel;
+mod mesh_shading;
+mod ray_tracing_interface;
+mod shader_groups;
+mod tessellation;
+mod topology;
 mod vertex_inputs;
 
 use entry_point::EntryPoint;
+use proc_macro2::TokenStream;
 use quote::{ToTokens, quote};
 use rspirv::dr::Module;
+use shader_groups::ShaderGroups;
+use spirv::ExecutionModel;
 
 use crate::types::FromInstruction;
 
@@ -30,14 +42,205 @@ impl FromSpirv for EntryPoints {
     }
 }
 
+impl EntryPoints {
+    pub fn execution_models(&self) -> impl Iterator<Item = ExecutionModel> {
+        self.entry_points
+            .iter()
+            .map(|entry_point| entry_point.execution_model)
+    }
+
+    /// Adds the stages of other modules the entry points may be linked with.
+    pub fn link_stages(&mut self, execution_models: &[ExecutionModel]) {
+        for entry_point in &mut self.entry_points {
+            for execution_model in execution_models {
+                if !entry_point.linked_stages.contains(execution_model) {
+                    entry_point.linked_stages.push(*execution_model);
+                }
+            }
+        }
+    }
+
+    /// The input assembly of a pipeline fed by this module's vertex shader. The first stage
+    /// consuming primitives decides the topology, without one it cannot be inferred.
+    fn input_assembly_tokens(entry_points: &[EntryPoint]) -> Option<TokenStream> {
+        if !entry_points
+            .iter()
+            .any(|entry_point| entry_point.execution_model == ExecutionModel::Vertex)
+        {
+            return None;
+        }
+
+        let consumer = entry_points.iter().find(|entry_point| {
+            entry_point
+                .tessellation
+                .as_ref()
+                .is_some_and(|tessellation| {
+                    tessellation.execution_model == ExecutionModel::TessellationControl
+                        && tessellation.output_vertices.is_some()
+                })
+        });
+        let consumer = consumer.or_else(|| {
+            entry_points.iter().find(|entry_point| {
+                entry_point
+                    .geometry
+                    .as_ref()
+                    .is_some_and(|geometry| geometry.input_topology.is_some())
+            })
+        });
+
+        let module_name = consumer?.module_name();
+
+        Some(quote! {
+            pub fn input_assembly_state_create_info<'a>() -> ash::vk::PipelineInputAssemblyStateCreateInfo<'a> {
+                #module_name::input_assembly_state_create_info()
+            }
+        })
+    }
+}
+
 impl ToTokens for EntryPoints {
-    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
         let entry_points = &self.entry_points;
+        let shader_groups = ShaderGroups::from_entry_points(entry_points);
+
+        // Kernels have no shader stage to create.
+        let stage_modules: Vec<_> = entry_points
+            .iter()
+            .filter(|entry_point| entry_point.execution_model != ExecutionModel::Kernel)
+            .map(|entry_point| entry_point.module_name())
+            .collect();
+        let stage_count = stage_modules.len();
+
+        let stage_create_infos = (stage_count != 0).then(|| {
+            quote! {
+                pub fn stage_create_infos<'a>(
+                    module: ash::vk::ShaderModule,
+                    spec: Option<&'a ash::vk::SpecializationInfo<'a>>,
+                ) -> [ash::vk::PipelineShaderStageCreateInfo<'a>; #stage_count] {
+                    [
+                        #( #stage_modules::stage_create_info(module, spec) ),*
+                    ]
+                }
+            }
+        });
+
+        let input_assembly = Self::input_assembly_tokens(entry_points);
 
         let new_tokens = quote! {
             #( #entry_points )*
+            #stage_create_infos
+            #input_assembly
+            #shader_groups
         };
 
         tokens.extend(new_tokens);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use rspirv::dr::Builder;
+    use spirv::{ExecutionMode, ExecutionModel};
+
+    use crate::{
+        components::FromSpirv,
+        utilities::{called_names, find_fn, load_built_module, parameter_names, parse_items},
+    };
+
+    use super::EntryPoints;
+
+    fn entry_points(execution_models: &[(ExecutionModel, &str)]) -> EntryPoints {
+        entry_points_with_modes(execution_models, &[])
+    }
+
+    fn entry_points_with_modes(
+        execution_models: &[(ExecutionModel, &str)],
+        execution_modes: &[(usize, ExecutionMode, &[u32])],
+    ) -> EntryPoints {
+        let mut builder = Builder::new();
+        let functions: Vec<_> = execution_models
+            .iter()
+            .map(|(execution_model, name)| {
+                let function = builder.id();
+                builder.entry_point(*execution_model, function, *name, []);
+                function
+            })
+            .collect();
+        for (index, execution_mode, operands) in execution_modes {
+            builder.execution_mode(functions[*index], *execution_mode, *operands);
+        }
+        let spirv = load_built_module(builder);
+
+        EntryPoints::from_spirv(&spirv).unwrap()
+    }
+
+    #[test]
+    fn stage_create_infos() {
+        let entry_points = entry_points(&[
+            (ExecutionModel::Vertex, "main"),
+            (ExecutionModel::Kernel, "reduce"),
+            (ExecutionModel::Fragment, "main"),
+        ]);
+        let items = parse_items(entry_points.to_token_stream());
+
+        let vertex = find_fn(&items, "vertex_main::stage_create_info").unwrap();
+        assert_eq!(parameter_names(&vertex), ["module", "spec"]);
+        assert!(find_fn(&items, "kernel_reduce::stage_create_info").is_none());
+
+        // Kernels have no stage, so only the vertex and fragment stages are created.
+        let stages = find_fn(&items, "stage_create_infos").unwrap();
+        assert_eq!(parameter_names(&stages), ["module", "spec"]);
+        assert_eq!(
+            called_names(&stages),
+            ["stage_create_info", "stage_create_info"]
+        );
+        assert_eq!(
+            stages.sig.output,
+            syn::parse_quote! {-> [ash::vk::PipelineShaderStageCreateInfo<'a>; 2usize]}
+        );
+    }
+
+    #[test]
+    fn input_assembly_from_consumer() {
+        let geometry = entry_points_with_modes(
+            &[
+                (ExecutionModel::Vertex, "main"),
+                (ExecutionModel::Geometry, "main"),
+            ],
+            &[(1, ExecutionMode::InputLines, &[])],
+        );
+        let items = parse_items(geometry.to_token_stream());
+        let input_assembly = find_fn(&items, "input_assembly_state_create_info").unwrap();
+        assert_eq!(
+            *input_assembly.block,
+            syn::parse_quote! {{geometry_main::input_assembly_state_create_info()}}
+        );
+
+        let tessellation = entry_points_with_modes(
+            &[
+                (ExecutionModel::Vertex, "main"),
+                (ExecutionModel::TessellationControl, "main"),
+            ],
+            &[(1, ExecutionMode::OutputVertices, &[3])],
+        );
+        let items = parse_items(tessellation.to_token_stream());
+        let input_assembly = find_fn(&items, "input_assembly_state_create_info").unwrap();
+        assert_eq!(
+            *input_assembly.block,
+            syn::parse_quote! {{tessellation_control_main::input_assembly_state_create_info()}}
+        );
+    }
+
+    #[test]
+    fn no_input_assembly_without_consumer() {
+        // The topology of a vertex shader feeding the rasterizer is up to the application.
+        let entry_points = entry_points(&[
+            (ExecutionModel::Vertex, "main"),
+            (ExecutionModel::Fragment, "main"),
+        ]);
+        let items = parse_items(entry_points.to_token_stream());
+
+        assert!(find_fn(&items, "input_assembly_state_create_info").is_none());
+    }
+}
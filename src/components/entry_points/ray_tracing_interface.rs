@@ -0,0 +1,229 @@
+use convert_case::{Case, Casing};
+use itertools::Itertools;
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use rspirv::dr::{Instruction, Module};
+use spirv::{ExecutionModel, Op, StorageClass};
+
+use crate::{
+    types::{FromInstruction, Structure, Type},
+    utilities::{find_instruction_with_id, find_name_for_id},
+};
+
+/// The ray tracing storage class variables in the interface of a ray tracing entry point.
+pub struct RayTracingInterface {
+    pub ray_payloads: Vec<Structure>,
+    pub incoming_ray_payload: Option<Structure>,
+    pub hit_attribute: Option<Structure>,
+    pub callable_data: Vec<Structure>,
+    pub incoming_callable_data: Option<Structure>,
+    pub shader_record: Option<Structure>,
+}
+
+impl RayTracingInterface {
+    /// Resolves the structure for a variable, wrapping types that are not a structure. Ray
+    /// tracing storage classes have no explicit layout, so structures use their natural layout.
+    fn variable_structure(variable: &Instruction, spirv: &Module) -> Option<Structure> {
+        // OpTypePointer | Result <id> | Storage Class | Type: <id>
+        let pointer = find_instruction_with_id(variable.result_type?, spirv)?;
+        let pointee = find_instruction_with_id(pointer.operands[1].unwrap_id_ref(), spirv)?;
+
+        match pointee.class.opcode {
+            Op::TypeStruct => Structure::from_natural_layout(pointee, spirv),
+            _ => {
+                let variable_type = Type::from_instruction(pointee, spirv)?;
+                let variable_id = variable.result_id?;
+                let name = find_name_for_id(variable_id, spirv)
+                    .unwrap_or_else(|| format!("variable_{}", variable_id));
+
+                Some(Structure::from_fields(
+                    vec![(variable_type, name.to_case(Case::Snake))],
+                    name,
+                ))
+            }
+        }
+    }
+
+    pub fn shader_record_size(&self) -> u32 {
+        self.shader_record
+            .as_ref()
+            .map(|structure| structure.layout.size() as u32)
+            .unwrap_or(0)
+    }
+}
+
+impl FromInstruction for RayTracingInterface {
+    fn from_instruction(instruction: &Instruction, spirv: &Module) -> Option<Self> {
+        // OpEntryPoint | Execution Model | Entry Point: <id> | Name: Literal | <id>...
+
+        if !matches!(instruction.class.opcode, Op::EntryPoint) {
+            return None;
+        }
+
+        if !matches!(
+            instruction.operands[0].unwrap_execution_model(),
+            ExecutionModel::RayGenerationKHR
+                | ExecutionModel::IntersectionKHR
+                | ExecutionModel::AnyHitKHR
+                | ExecutionModel::ClosestHitKHR
+                | ExecutionModel::MissKHR
+                | ExecutionModel::CallableKHR
+        ) {
+            return None;
+        }
+
+        // OpVariable | Result Type: <id> | Result <id> | Storage Class
+        let variables: Vec<_> = instruction.operands[3..]
+            .iter()
+            .filter_map(|operand| find_instruction_with_id(operand.unwrap_id_ref(), spirv))
+            .filter(|variable| matches!(variable.class.opcode, Op::Variable))
+            .collect();
+
+        let of_storage_class = |storage_class: StorageClass| -> Vec<Structure> {
+            variables
+                .iter()
+                .filter(|variable| variable.operands[0].unwrap_storage_class() == storage_class)
+                .filter_map(|variable| Self::variable_structure(variable, spirv))
+                .unique_by(|structure| structure.name.clone())
+                .collect()
+        };
+
+        Some(Self {
+            ray_payloads: of_storage_class(StorageClass::RayPayloadKHR),
+            incoming_ray_payload: of_storage_class(StorageClass::IncomingRayPayloadKHR)
+                .into_iter()
+                .next(),
+            hit_attribute: of_storage_class(StorageClass::HitAttributeKHR)
+                .into_iter()
+                .next(),
+            callable_data: of_storage_class(StorageClass::CallableDataKHR),
+            incoming_callable_data: of_storage_class(StorageClass::IncomingCallableDataKHR)
+                .into_iter()
+                .next(),
+            shader_record: of_storage_class(StorageClass::ShaderRecordBufferKHR)
+                .into_iter()
+                .next(),
+        })
+    }
+}
+
+impl ToTokens for RayTracingInterface {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let size_constant = |structure: &Option<Structure>, name: TokenStream| {
+            structure.as_ref().map(|structure| {
+                let size = structure.layout.size() as u32;
+                quote! {pub const #name: u32 = #size;}
+            })
+        };
+
+        let incoming_ray_payload_size = size_constant(
+            &self.incoming_ray_payload,
+            quote! {INCOMING_RAY_PAYLOAD_SIZE},
+        );
+        let hit_attribute_size = size_constant(&self.hit_attribute, quote! {HIT_ATTRIBUTE_SIZE});
+        let incoming_callable_data_size = size_constant(
+            &self.incoming_callable_data,
+            quote! {INCOMING_CALLABLE_DATA_SIZE},
+        );
+        let shader_record_size = size_constant(&self.shader_record, quote! {SHADER_RECORD_SIZE});
+
+        let limits = self.hit_attribute.as_ref().map(|_| {
+            quote! {
+                pub fn within_ray_tracing_limits(
+                    properties: &ash::vk::PhysicalDeviceRayTracingPipelinePropertiesKHR<'_>,
+                ) -> bool {
+                    HIT_ATTRIBUTE_SIZE <= properties.max_ray_hit_attribute_size
+                }
+            }
+        });
+
+        // Structures are only emitted once, the same type may be used for several roles.
+        let structures = self
+            .ray_payloads
+            .iter()
+            .chain(&self.incoming_ray_payload)
+            .chain(&self.hit_attribute)
+            .chain(&self.callable_data)
+            .chain(&self.incoming_callable_data)
+            .chain(&self.shader_record)
+            .unique_by(|structure| structure.name.clone());
+
+        let new_tokens = quote! {
+            #( #structures )*
+            #incoming_ray_payload_size
+            #hit_attribute_size
+            #incoming_callable_data_size
+            #shader_record_size
+            #limits
+        };
+
+        tokens.extend(new_tokens);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use rspirv::dr::Builder;
+    use spirv::{ExecutionModel, StorageClass};
+
+    use crate::{
+        types::FromInstruction,
+        utilities::{find_const, find_fn, load_built_module, parse_items},
+    };
+
+    use super::RayTracingInterface;
+
+    #[test]
+    fn payload_and_hit_attribute() {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        let float = builder.type_float(32);
+        let vec2 = builder.type_vector(float, 2);
+        let vec3 = builder.type_vector(float, 3);
+        let payload = builder.type_struct([vec3, float]);
+        builder.name(payload, "HitInfo");
+
+        let payload_pointer =
+            builder.type_pointer(None, StorageClass::IncomingRayPayloadKHR, payload);
+        let payload_variable = builder.variable(
+            payload_pointer,
+            None,
+            StorageClass::IncomingRayPayloadKHR,
+            None,
+        );
+        let attribute_pointer = builder.type_pointer(None, StorageClass::HitAttributeKHR, vec2);
+        let attribute_variable =
+            builder.variable(attribute_pointer, None, StorageClass::HitAttributeKHR, None);
+        builder.name(attribute_variable, "barycentrics");
+
+        builder.entry_point(
+            ExecutionModel::ClosestHitKHR,
+            main,
+            "main",
+            [payload_variable, attribute_variable],
+        );
+        let spirv = load_built_module(builder);
+
+        let interface =
+            RayTracingInterface::from_instruction(&spirv.entry_points[0], &spirv).unwrap();
+
+        let payload = interface.incoming_ray_payload.as_ref().unwrap();
+        assert_eq!(payload.name, "HitInfo");
+        assert_eq!(payload.layout.size(), 16);
+
+        let hit_attribute = interface.hit_attribute.as_ref().unwrap();
+        assert_eq!(hit_attribute.name, "barycentrics");
+        assert_eq!(hit_attribute.layout.size(), 8);
+
+        assert_eq!(interface.shader_record_size(), 0);
+
+        let items = parse_items(interface.to_token_stream());
+        assert_eq!(
+            find_const(&items, "HIT_ATTRIBUTE_SIZE"),
+            Some(syn::parse_quote! {8u32})
+        );
+        assert!(find_const(&items, "SHADER_RECORD_SIZE").is_none());
+        assert!(find_fn(&items, "within_ray_tracing_limits").is_some());
+    }
+}
@@ -0,0 +1,440 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use spirv::ExecutionModel;
+
+use super::entry_point::EntryPoint;
+
+/// A ray tracing shader group, referencing stages by their index in `stage_create_infos`.
+pub enum ShaderGroup {
+    General(usize),
+    TrianglesHit {
+        closest_hit: Option<usize>,
+        any_hit: Option<usize>,
+    },
+    ProceduralHit {
+        closest_hit: Option<usize>,
+        any_hit: Option<usize>,
+        intersection: usize,
+    },
+}
+
+impl ToTokens for ShaderGroup {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let index_tokens = |index: Option<usize>| match index {
+            Some(index) => {
+                let index = index as u32;
+                quote! {#index}
+            }
+            None => quote! {ash::vk::SHADER_UNUSED_KHR},
+        };
+
+        let (group_type, general, closest_hit, any_hit, intersection) = match self {
+            Self::General(index) => (
+                quote! {ash::vk::RayTracingShaderGroupTypeKHR::GENERAL},
+                Some(*index),
+                None,
+                None,
+                None,
+            ),
+            Self::TrianglesHit {
+                closest_hit,
+                any_hit,
+            } => (
+                quote! {ash::vk::RayTracingShaderGroupTypeKHR::TRIANGLES_HIT_GROUP},
+                None,
+                *closest_hit,
+                *any_hit,
+                None,
+            ),
+            Self::ProceduralHit {
+                closest_hit,
+                any_hit,
+                intersection,
+            } => (
+                quote! {ash::vk::RayTracingShaderGroupTypeKHR::PROCEDURAL_HIT_GROUP},
+                None,
+                *closest_hit,
+                *any_hit,
+                Some(*intersection),
+            ),
+        };
+
+        let general = index_tokens(general);
+        let closest_hit = index_tokens(closest_hit);
+        let any_hit = index_tokens(any_hit);
+        let intersection = index_tokens(intersection);
+
+        let new_tokens = quote! {
+            ash::vk::RayTracingShaderGroupCreateInfoKHR::default()
+                .ty(#group_type)
+                .general_shader(#general)
+                .closest_hit_shader(#closest_hit)
+                .any_hit_shader(#any_hit)
+                .intersection_shader(#intersection)
+        };
+
+        tokens.extend(new_tokens);
+    }
+}
+
+/// The ray tracing entry points of a module, grouped for a ray tracing pipeline.
+pub struct ShaderGroups {
+    pub raygen: Vec<ShaderGroup>,
+    pub miss: Vec<ShaderGroup>,
+    pub hit: Vec<ShaderGroup>,
+    pub callable: Vec<ShaderGroup>,
+    pub record_sizes: [u32; 4],
+}
+
+impl ShaderGroups {
+    pub fn from_entry_points(entry_points: &[EntryPoint]) -> Option<Self> {
+        let of_model = |execution_model: ExecutionModel| -> Vec<&EntryPoint> {
+            entry_points
+                .iter()
+                .filter(|entry_point| entry_point.execution_model == execution_model)
+                .collect()
+        };
+
+        // Every record in a region has the same stride, so it must fit the largest record.
+        let record_size = |entry_points: &[&EntryPoint]| -> u32 {
+            entry_points
+                .iter()
+                .filter_map(|entry_point| entry_point.ray_tracing.as_ref())
+                .map(|interface| interface.shader_record_size())
+                .max()
+                .unwrap_or(0)
+        };
+
+        let raygen = of_model(ExecutionModel::RayGenerationKHR);
+        let miss = of_model(ExecutionModel::MissKHR);
+        let closest_hit = of_model(ExecutionModel::ClosestHitKHR);
+        let any_hit = of_model(ExecutionModel::AnyHitKHR);
+        let intersection = of_model(ExecutionModel::IntersectionKHR);
+        let callable = of_model(ExecutionModel::CallableKHR);
+
+        let record_sizes = [
+            record_size(&raygen),
+            record_size(&miss),
+            record_size(&[closest_hit.as_slice(), &any_hit, &intersection].concat()),
+            record_size(&callable),
+        ];
+
+        // Stages are indexed as in `stage_create_infos`, which skips kernels.
+        let push_stage = |entry_point: &EntryPoint| -> usize {
+            entry_points
+                .iter()
+                .filter(|other| other.execution_model != ExecutionModel::Kernel)
+                .position(|other| core::ptr::eq(other, entry_point))
+                .expect("Ray tracing entry points must have a stage")
+        };
+
+        let raygen_groups: Vec<_> = raygen
+            .into_iter()
+            .map(|entry_point| ShaderGroup::General(push_stage(entry_point)))
+            .collect();
+
+        let miss_groups: Vec<_> = miss
+            .into_iter()
+            .map(|entry_point| ShaderGroup::General(push_stage(entry_point)))
+            .collect();
+
+        // A module with at most one of each hit stage forms a single hit group, otherwise there
+        // is no way to tell which stages belong together, so each forms its own hit group.
+        let hit_groups: Vec<_> = if closest_hit.len() <= 1
+            && any_hit.len() <= 1
+            && intersection.len() <= 1
+        {
+            let closest_hit = closest_hit
+                .first()
+                .map(|entry_point| push_stage(entry_point));
+            let any_hit = any_hit.first().map(|entry_point| push_stage(entry_point));
+            let intersection = intersection
+                .first()
+                .map(|entry_point| push_stage(entry_point));
+
+            match (closest_hit, any_hit, intersection) {
+                (None, None, None) => vec![],
+                (closest_hit, any_hit, Some(intersection)) => vec![ShaderGroup::ProceduralHit {
+                    closest_hit,
+                    any_hit,
+                    intersection,
+                }],
+                (closest_hit, any_hit, None) => vec![ShaderGroup::TrianglesHit {
+                    closest_hit,
+                    any_hit,
+                }],
+            }
+        } else {
+            let closest_hit_groups: Vec<_> = closest_hit
+                .into_iter()
+                .map(|entry_point| ShaderGroup::TrianglesHit {
+                    closest_hit: Some(push_stage(entry_point)),
+                    any_hit: None,
+                })
+                .collect();
+            let any_hit_groups: Vec<_> = any_hit
+                .into_iter()
+                .map(|entry_point| ShaderGroup::TrianglesHit {
+                    closest_hit: None,
+                    any_hit: Some(push_stage(entry_point)),
+                })
+                .collect();
+            let intersection_groups: Vec<_> = intersection
+                .into_iter()
+                .map(|entry_point| ShaderGroup::ProceduralHit {
+                    closest_hit: None,
+                    any_hit: None,
+                    intersection: push_stage(entry_point),
+                })
+                .collect();
+
+            closest_hit_groups
+                .into_iter()
+                .chain(any_hit_groups)
+                .chain(intersection_groups)
+                .collect()
+        };
+
+        let callable_groups: Vec<_> = callable
+            .into_iter()
+            .map(|entry_point| ShaderGroup::General(push_stage(entry_point)))
+            .collect();
+
+        if raygen_groups.is_empty()
+            && miss_groups.is_empty()
+            && hit_groups.is_empty()
+            && callable_groups.is_empty()
+        {
+            return None;
+        }
+
+        Some(Self {
+            raygen: raygen_groups,
+            miss: miss_groups,
+            hit: hit_groups,
+            callable: callable_groups,
+            record_sizes,
+        })
+    }
+}
+
+impl ToTokens for ShaderGroups {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let raygen_count = self.raygen.len() as u32;
+        let miss_count = self.miss.len() as u32;
+        let hit_count = self.hit.len() as u32;
+        let callable_count = self.callable.len() as u32;
+
+        let [
+            raygen_record_size,
+            miss_record_size,
+            hit_record_size,
+            callable_record_size,
+        ] = self.record_sizes;
+
+        let groups: Vec<_> = self
+            .raygen
+            .iter()
+            .chain(&self.miss)
+            .chain(&self.hit)
+            .chain(&self.callable)
+            .collect();
+        let group_count = groups.len();
+        let shader_group_count = group_count as u32;
+
+        // The shader binding table layout holds the group handles in the same order as the groups,
+        // with each region's `device_address` relative to the start of the table.
+        let new_tokens = quote! {
+            pub const RAYGEN_GROUP_COUNT: u32 = #raygen_count;
+            pub const MISS_GROUP_COUNT: u32 = #miss_count;
+            pub const HIT_GROUP_COUNT: u32 = #hit_count;
+            pub const CALLABLE_GROUP_COUNT: u32 = #callable_count;
+            pub const SHADER_GROUP_COUNT: u32 = #shader_group_count;
+
+            pub const RAYGEN_RECORD_SIZE: u32 = #raygen_record_size;
+            pub const MISS_RECORD_SIZE: u32 = #miss_record_size;
+            pub const HIT_RECORD_SIZE: u32 = #hit_record_size;
+            pub const CALLABLE_RECORD_SIZE: u32 = #callable_record_size;
+
+            pub fn ray_tracing_shader_groups<'a>() -> [ash::vk::RayTracingShaderGroupCreateInfoKHR<'a>; #group_count] {
+                [
+                    #( #groups ),*
+                ]
+            }
+
+            #[derive(Clone, Copy, Default)]
+            pub struct ShaderBindingTableLayout {
+                pub handle_size: u64,
+                pub raygen: ash::vk::StridedDeviceAddressRegionKHR,
+                pub miss: ash::vk::StridedDeviceAddressRegionKHR,
+                pub hit: ash::vk::StridedDeviceAddressRegionKHR,
+                pub callable: ash::vk::StridedDeviceAddressRegionKHR,
+                pub size: u64,
+            }
+
+            impl ShaderBindingTableLayout {
+                pub fn new(properties: &ash::vk::PhysicalDeviceRayTracingPipelinePropertiesKHR<'_>) -> Self {
+                    let handle_size = properties.shader_group_handle_size as u64;
+                    let handle_alignment = properties.shader_group_handle_alignment as u64;
+                    let base_alignment = properties.shader_group_base_alignment as u64;
+
+                    let mut offset: u64 = 0;
+                    let mut region = |count: u32, stride: u64| {
+                        if count == 0 {
+                            return ash::vk::StridedDeviceAddressRegionKHR::default();
+                        }
+
+                        let region = ash::vk::StridedDeviceAddressRegionKHR::default()
+                            .device_address(offset.next_multiple_of(base_alignment))
+                            .stride(stride)
+                            .size(count as u64 * stride);
+
+                        offset = region.device_address + region.size;
+                        region
+                    };
+
+                    let stride = |record_size: u32, alignment: u64| {
+                        (handle_size + record_size as u64).next_multiple_of(alignment)
+                    };
+
+                    // Each raygen record must start at the base alignment, as it is its own region.
+                    let raygen = region(RAYGEN_GROUP_COUNT, stride(RAYGEN_RECORD_SIZE, base_alignment));
+                    let miss = region(MISS_GROUP_COUNT, stride(MISS_RECORD_SIZE, handle_alignment));
+                    let hit = region(HIT_GROUP_COUNT, stride(HIT_RECORD_SIZE, handle_alignment));
+                    let callable = region(CALLABLE_GROUP_COUNT, stride(CALLABLE_RECORD_SIZE, handle_alignment));
+
+                    let size = offset;
+
+                    Self {
+                        handle_size,
+                        raygen,
+                        miss,
+                        hit,
+                        callable,
+                        size,
+                    }
+                }
+
+                pub fn regions(
+                    &self,
+                    address: ash::vk::DeviceAddress,
+                    raygen_index: u32,
+                ) -> [ash::vk::StridedDeviceAddressRegionKHR; 4] {
+                    let absolute = |region: ash::vk::StridedDeviceAddressRegionKHR| {
+                        if region.size == 0 {
+                            region
+                        } else {
+                            region.device_address(address + region.device_address)
+                        }
+                    };
+
+                    let raygen = absolute(self.raygen);
+                    let raygen = raygen
+                        .device_address(raygen.device_address + raygen_index as u64 * raygen.stride)
+                        .size(raygen.stride);
+
+                    [raygen, absolute(self.miss), absolute(self.hit), absolute(self.callable)]
+                }
+
+                pub fn write_handles(&self, handles: &[u8], table: &mut [u8]) {
+                    let handle_size = self.handle_size as usize;
+                    let regions = [
+                        (self.raygen, RAYGEN_GROUP_COUNT),
+                        (self.miss, MISS_GROUP_COUNT),
+                        (self.hit, HIT_GROUP_COUNT),
+                        (self.callable, CALLABLE_GROUP_COUNT),
+                    ];
+
+                    let mut group = 0;
+                    for (region, count) in regions {
+                        for index in 0..count as u64 {
+                            let source = group * handle_size;
+                            let destination = (region.device_address + index * region.stride) as usize;
+
+                            table[destination..destination + handle_size]
+                                .copy_from_slice(&handles[source..source + handle_size]);
+
+                            group += 1;
+                        }
+                    }
+                }
+
+                pub fn write_record<T: bytemuck::Pod>(
+                    &self,
+                    table: &mut [u8],
+                    region: ash::vk::StridedDeviceAddressRegionKHR,
+                    index: u32,
+                    record: &T,
+                ) {
+                    let record = bytemuck::bytes_of(record);
+                    let offset = (region.device_address + index as u64 * region.stride + self.handle_size) as usize;
+
+                    table[offset..offset + record.len()].copy_from_slice(record);
+                }
+            }
+        };
+
+        tokens.extend(new_tokens);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rspirv::dr::Builder;
+    use spirv::ExecutionModel;
+
+    use crate::{
+        components::{EntryPoints, FromSpirv},
+        utilities::load_built_module,
+    };
+
+    use super::{ShaderGroup, ShaderGroups};
+
+    #[test]
+    fn groups_index_stages() {
+        let mut builder = Builder::new();
+        for (execution_model, name) in [
+            (ExecutionModel::RayGenerationKHR, "raygen"),
+            (ExecutionModel::MissKHR, "miss"),
+            (ExecutionModel::ClosestHitKHR, "closest"),
+            (ExecutionModel::AnyHitKHR, "any"),
+        ] {
+            let function = builder.id();
+            builder.entry_point(execution_model, function, name, []);
+        }
+        let spirv = load_built_module(builder);
+
+        let entry_points = EntryPoints::from_spirv(&spirv).unwrap();
+        let groups = ShaderGroups::from_entry_points(&entry_points.entry_points).unwrap();
+
+        assert!(matches!(
+            groups.raygen.as_slice(),
+            [ShaderGroup::General(0)]
+        ));
+        assert!(matches!(groups.miss.as_slice(), [ShaderGroup::General(1)]));
+        assert!(matches!(
+            groups.hit.as_slice(),
+            [ShaderGroup::TrianglesHit {
+                closest_hit: Some(2),
+                any_hit: Some(3),
+            }]
+        ));
+        assert!(groups.callable.is_empty());
+        assert_eq!(groups.record_sizes, [0; 4]);
+
+        let module_names: Vec<_> = entry_points
+            .entry_points
+            .iter()
+            .map(|entry_point| entry_point.module_name().to_string())
+            .collect();
+        assert_eq!(
+            module_names,
+            [
+                "ray_generation_nv_raygen",
+                "miss_nv_miss",
+                "closest_hit_nv_closest",
+                "any_hit_nv_any"
+            ]
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use quote::{ToTokens, quote};
+use rspirv::dr::{Instruction, Module};
+use spirv::{ExecutionMode, ExecutionModel, Op};
+
+use crate::{
+    types::{FromInstruction, TypeSyntax},
+    utilities::find_execution_mode,
+};
+
+use super::{execution_mode_flags::ExecutionModeFlags, topology::Topology};
+
+/// The tessellation execution modes, which may be declared on either tessellation stage.
+const TESSELLATION_MODES: [(&str, ExecutionMode); 9] = [
+    ("TRIANGLES", ExecutionMode::Triangles),
+    ("QUADS", ExecutionMode::Quads),
+    ("ISOLINES", ExecutionMode::Isolines),
+    ("SPACING_EQUAL", ExecutionMode::SpacingEqual),
+    (
+        "SPACING_FRACTIONAL_EVEN",
+        ExecutionMode::SpacingFractionalEven,
+    ),
+    (
+        "SPACING_FRACTIONAL_ODD",
+        ExecutionMode::SpacingFractionalOdd,
+    ),
+    ("VERTEX_ORDER_CW", ExecutionMode::VertexOrderCw),
+    ("VERTEX_ORDER_CCW", ExecutionMode::VertexOrderCcw),
+    ("POINT_MODE", ExecutionMode::PointMode),
+];
+
+/// The execution modes of a tessellation control or evaluation entry point.
+pub struct Tessellation {
+    pub execution_model: ExecutionModel,
+    pub output_vertices: Option<u32>,
+    pub flags: ExecutionModeFlags,
+}
+
+impl FromInstruction for Tessellation {
+    fn from_instruction(instruction: &Instruction, spirv: &Module) -> Option<Self> {
+        // OpEntryPoint | Execution Model | Entry Point: <id> | Name: Literal | <id>...
+
+        if !matches!(instruction.class.opcode, Op::EntryPoint) {
+            return None;
+        }
+
+        let execution_model = instruction.operands[0].unwrap_execution_model();
+        if !matches!(
+            execution_model,
+            ExecutionModel::TessellationControl | ExecutionModel::TessellationEvaluation
+        ) {
+            return None;
+        }
+
+        let entry_point_id = instruction.operands[1].unwrap_id_ref();
+
+        // The number of control points in the output patch.
+        let output_vertices =
+            find_execution_mode(entry_point_id, ExecutionMode::OutputVertices, spirv)
+                .map(|operands| operands[0].unwrap_literal_bit32());
+
+        let flags = ExecutionModeFlags::for_entrypoint(entry_point_id, &TESSELLATION_MODES, spirv);
+
+        Some(Self {
+            execution_model,
+            output_vertices,
+            flags,
+        })
+    }
+}
+
+impl ToTokens for Tessellation {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let flags = &self.flags;
+
+        let output_vertices = self.output_vertices.map(|output_vertices| {
+            quote! {pub const OUTPUT_VERTICES: u32 = #output_vertices;}
+        });
+
+        // The control shader consumes the patches, its output patch size is used as the input
+        // patch size.
+        let create_info = match (self.execution_model, self.output_vertices) {
+            (ExecutionModel::TessellationControl, Some(_)) => {
+                let topology = Topology::PatchList.to_type_syntax();
+
+                Some(quote! {
+                    pub const INPUT_TOPOLOGY: ash::vk::PrimitiveTopology = #topology;
+
+                    pub fn input_assembly_state_create_info<'a>() -> ash::vk::PipelineInputAssemblyStateCreateInfo<'a> {
+                        ash::vk::PipelineInputAssemblyStateCreateInfo::default()
+                            .topology(INPUT_TOPOLOGY)
+                    }
+
+                    pub fn tessellation_state_create_info<'a>() -> ash::vk::PipelineTessellationStateCreateInfo<'a> {
+                        ash::vk::PipelineTessellationStateCreateInfo::default()
+                            .patch_control_points(OUTPUT_VERTICES)
+                    }
+                })
+            }
+            _ => None,
+        };
+
+        let new_tokens = quote! {
+            #output_vertices
+            #flags
+            #create_info
+        };
+
+        tokens.extend(new_tokens);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::ToTokens;
+    use rspirv::dr::Builder;
+    use spirv::{ExecutionMode, ExecutionModel};
+
+    use crate::{
+        types::FromInstruction,
+        utilities::{
+            called_names, find_const, find_fn, load_built_module, parameter_names, parse_items,
+            referenced_names,
+        },
+    };
+
+    use super::Tessellation;
+
+    fn tessellation(
+        execution_model: ExecutionModel,
+        modes: &[(ExecutionMode, &[u32])],
+    ) -> Tessellation {
+        let mut builder = Builder::new();
+        let main = builder.id();
+        builder.entry_point(execution_model, main, "main", []);
+        for (mode, operands) in modes {
+            builder.execution_mode(main, *mode, *operands);
+        }
+        let spirv = load_built_module(builder);
+
+        Tessellation::from_instruction(&spirv.entry_points[0], &spirv).unwrap()
+    }
+
+    #[test]
+    fn control_patch_size() {
+        let tessellation = tessellation(
+            ExecutionModel::TessellationControl,
+            &[(ExecutionMode::OutputVertices, &[3])],
+        );
+        assert_eq!(tessellation.output_vertices, Some(3));
+
+        let items = parse_items(tessellation.to_token_stream());
+        assert_eq!(
+            find_const(&items, "OUTPUT_VERTICES"),
+            Some(syn::parse_quote! {3u32})
+        );
+
+        let create_info = find_fn(&items, "tessellation_state_create_info").unwrap();
+        assert!(parameter_names(&create_info).is_empty());
+        assert_eq!(
+            called_names(&create_info),
+            ["default", "patch_control_points"]
+        );
+        assert!(referenced_names(&create_info).contains(&"OUTPUT_VERTICES".to_string()));
+    }
+
+    #[test]
+    fn evaluation_has_no_create_info() {
+        let tessellation = tessellation(
+            ExecutionModel::TessellationEvaluation,
+            &[(ExecutionMode::Triangles, &[])],
+        );
+        assert_eq!(tessellation.output_vertices, None);
+
+        let items = parse_items(tessellation.to_token_stream());
+        assert!(find_fn(&items, "tessellation_state_create_info").is_none());
+        assert!(find_fn(&items, "input_assembly_state_create_info").is_none());
+    }
+}
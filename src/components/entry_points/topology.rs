@@ -0,0 +1,32 @@
+use crate::types::TypeSyntax;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topology {
+    PointList,
+    LineList,
+    LineStrip,
+    TriangleList,
+    TriangleStrip,
+    LineListWithAdjacency,
+    TriangleListWithAdjacency,
+    PatchList,
+}
+
+impl TypeSyntax for Topology {
+    fn to_type_syntax(&self) -> syn::Type {
+        match self {
+            Self::PointList => syn::parse_quote!(ash::vk::PrimitiveTopology::POINT_LIST),
+            Self::LineList => syn::parse_quote!(ash::vk::PrimitiveTopology::LINE_LIST),
+            Self::LineStrip => syn::parse_quote!(ash::vk::PrimitiveTopology::LINE_STRIP),
+            Self::TriangleList => syn::parse_quote!(ash::vk::PrimitiveTopology::TRIANGLE_LIST),
+            Self::TriangleStrip => syn::parse_quote!(ash::vk::PrimitiveTopology::TRIANGLE_STRIP),
+            Self::LineListWithAdjacency => {
+                syn::parse_quote!(ash::vk::PrimitiveTopology::LINE_LIST_WITH_ADJACENCY)
+            }
+            Self::TriangleListWithAdjacency => {
+                syn::parse_quote!(ash::vk::PrimitiveTopology::TRIANGLE_LIST_WITH_ADJACENCY)
+            }
+            Self::PatchList => syn::parse_quote!(ash::vk::PrimitiveTopology::PATCH_LIST),
+        }
+    }
+}
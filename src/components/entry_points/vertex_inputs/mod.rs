@@ -198,6 +198,44 @@ impl ToTokens for VertexInputs {
             }
         };
 
+        // Owns both description arrays, so the create info can borrow them.
+        let state_tokens = {
+            let binding_count = [&self.vertex_inputs, &self.instance_inputs]
+                .iter()
+                .filter(|group| group.is_some())
+                .count();
+            let attribute_count: usize = [&self.vertex_inputs, &self.instance_inputs]
+                .iter()
+                .filter_map(|group| group.as_ref())
+                .map(|group| group.attribute_count())
+                .sum();
+
+            quote! {
+                #[derive(Clone, Copy)]
+                pub struct VertexInputState {
+                    pub bindings: [ash::vk::VertexInputBindingDescription; #binding_count],
+                    pub attributes: [ash::vk::VertexInputAttributeDescription; #attribute_count],
+                }
+
+                impl Default for VertexInputState {
+                    fn default() -> Self {
+                        Self {
+                            bindings: vertex_binding_descriptions(),
+                            attributes: vertex_attribute_descriptions(),
+                        }
+                    }
+                }
+
+                impl VertexInputState {
+                    pub fn info(&self) -> ash::vk::PipelineVertexInputStateCreateInfo<'_> {
+                        ash::vk::PipelineVertexInputStateCreateInfo::default()
+                            .vertex_binding_descriptions(&self.bindings)
+                            .vertex_attribute_descriptions(&self.attributes)
+                    }
+                }
+            }
+        };
+
         let vertex_structure = self.vertex_inputs.as_ref().map(|group| &group.structure);
         let instance_structure = self.instance_inputs.as_ref().map(|group| &group.structure);
 
@@ -208,6 +246,7 @@ impl ToTokens for VertexInputs {
             #attribute_tokens
             #binding_tokens_2_ext
             #attribute_tokens_2_ext
+            #state_tokens
         };
 
         tokens.extend(new_tokens);
@@ -1,4 +1,4 @@
-pub use descriptors::DescriptorSets;
+pub use descriptors::{DescriptorSetError, DescriptorSets};
 pub use entry_points::EntryPoints;
 pub use push_constants::PushConstants;
 pub use specialization_constants::SpecializationConstants;
@@ -32,8 +32,17 @@ impl ToTokens for PushConstants {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let push_constants = &self.push_constants;
 
+        let range_count = push_constants.len();
+        let names = push_constants
+            .iter()
+            .map(|push_constant| push_constant.structure.name_ident());
+
         let new_tokens = quote! {
             #( #push_constants )*
+
+            pub const PUSH_CONSTANT_RANGES: [ash::vk::PushConstantRange; #range_count] = [
+                #( #names::PUSH_CONSTANT_RANGE ),*
+            ];
         };
 
         tokens.extend(new_tokens);
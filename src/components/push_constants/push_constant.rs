@@ -4,7 +4,7 @@ use spirv::{ExecutionModel, Op, StorageClass};
 
 use crate::{
     types::{FromInstruction, Structure, Type},
-    utilities::{execution_model_to_tokens, find_instruction_with_id, variable_execution_models},
+    utilities::{execution_models_to_tokens, find_instruction_with_id, variable_execution_models},
 };
 
 pub struct PushConstant {
@@ -55,20 +55,22 @@ impl ToTokens for PushConstant {
         let name = self.structure.name_ident();
         let size = self.structure.layout.size() as u32;
 
-        let stage_tokens_1 = self.stages.iter().map(execution_model_to_tokens);
-        let stage_tokens_2 = stage_tokens_1.clone();
+        let stage_tokens = execution_models_to_tokens(&self.stages);
 
         let new_tokens = quote! {
             #structure
 
             impl #name {
-                pub const STAGES: ash::vk::ShaderStageFlags = #( #stage_tokens_1 )|* ;
+                pub const STAGES: ash::vk::ShaderStageFlags = #stage_tokens;
+
+                pub const PUSH_CONSTANT_RANGE: ash::vk::PushConstantRange = ash::vk::PushConstantRange {
+                    stage_flags: Self::STAGES,
+                    offset: 0,
+                    size: #size,
+                };
 
                 pub fn push_constant_range() -> ash::vk::PushConstantRange {
-                    ash::vk::PushConstantRange::default()
-                        .offset(0)
-                        .size( #size )
-                        .stage_flags( #( #stage_tokens_2 )|* )
+                    Self::PUSH_CONSTANT_RANGE
                 }
             }
         };
@@ -2,9 +2,12 @@ use itertools::Itertools;
 use proc_macro2::TokenStream;
 use quote::{ToTokens, quote};
 use rspirv::dr::Module;
-use specialization_constant::SpecializationConstant;
+pub use specialization_constant::SpecializationConstant;
 
-use crate::types::{FromInstruction, SizedType, Structure};
+use crate::{
+    types::{FromInstruction, SizedType, Structure},
+    utilities::is_kernel_module,
+};
 
 use super::FromSpirv;
 
@@ -13,6 +16,7 @@ mod specialization_constant;
 #[derive(Debug)]
 pub struct SpecializationConstants {
     pub constants: Vec<SpecializationConstant>,
+    pub is_kernel: bool,
 }
 
 impl FromSpirv for SpecializationConstants {
@@ -28,7 +32,10 @@ impl FromSpirv for SpecializationConstants {
             return None;
         }
 
-        Some(Self { constants })
+        Some(Self {
+            constants,
+            is_kernel: is_kernel_module(spirv),
+        })
     }
 }
 
@@ -68,7 +75,8 @@ impl ToTokens for SpecializationConstants {
             }
         });
 
-        let impl_tokens = {
+        // OpenCL sets each constant individually, without a map.
+        let impl_tokens = (!self.is_kernel).then(|| {
             let map_entry_count = map_entries.len();
 
             quote! {
@@ -80,7 +88,7 @@ impl ToTokens for SpecializationConstants {
                     }
                 }
             }
-        };
+        });
 
         let new_tokens = quote! {
             #structure
@@ -6,13 +6,27 @@ mod components;
 mod types;
 mod utilities;
 
+pub use components::DescriptorSetError;
+
 use components::{DescriptorSets, EntryPoints, FromSpirv, PushConstants, SpecializationConstants};
 use proc_macro2::TokenStream;
 use quote::{ToTokens, quote};
-use rspirv::{
-    binary::{ParseState, Parser},
-    dr::Loader,
-};
+use rspirv::binary::{ParseState, Parser};
+use spirv::ExecutionModel;
+use thiserror::Error;
+use utilities::{ModuleLoader, is_kernel_module};
+
+/// An error while loading a SPIR-V document.
+#[derive(Debug, Error)]
+pub enum ShaderError {
+    /// The document is not valid SPIR-V.
+    #[error(transparent)]
+    Parse(#[from] ParseState),
+
+    /// The descriptor sets cannot be used as reflected.
+    #[error(transparent)]
+    DescriptorSets(#[from] DescriptorSetError),
+}
 
 /// A parsed SPIR-V document to generate bindings from.
 pub struct Shader {
@@ -27,13 +41,16 @@ pub struct Shader {
 
     /// The shader's descriptor sets.
     pub descriptor_sets: Option<DescriptorSets>,
+
+    /// Whether the shader is an OpenCL module rather than a Vulkan one.
+    pub is_kernel: bool,
 }
 
 impl Shader {
     /// Load a SPIR-V document from it's bytes.
-    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, ParseState> {
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, ShaderError> {
         let spirv = {
-            let mut loader = Loader::new();
+            let mut loader = ModuleLoader::new();
             let p = Parser::new(bytes, &mut loader);
             p.parse()?;
             loader.module()
@@ -43,16 +60,39 @@ impl Shader {
         let entry_points = EntryPoints::from_spirv(&spirv);
         let push_constants = PushConstants::from_spirv(&spirv);
         let descriptor_sets = DescriptorSets::from_spirv(&spirv);
+        if let Some(descriptor_sets) = &descriptor_sets {
+            descriptor_sets.validate()?;
+        }
+        let is_kernel = is_kernel_module(&spirv);
 
         Ok(Self {
             specialization_constants,
             entry_points,
             push_constants,
             descriptor_sets,
+            is_kernel,
         })
     }
 }
 
+impl Shader {
+    /// The execution models of the shader's entry points.
+    pub fn execution_models(&self) -> Vec<ExecutionModel> {
+        self.entry_points
+            .iter()
+            .flat_map(|entry_points| entry_points.execution_models())
+            .collect()
+    }
+
+    /// Infer the stages following each entry point from the stages of every linked shader, such
+    /// as the other shaders of a directory.
+    pub fn link_stages(&mut self, execution_models: &[ExecutionModel]) {
+        if let Some(entry_points) = &mut self.entry_points {
+            entry_points.link_stages(execution_models);
+        }
+    }
+}
+
 impl ToTokens for Shader {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let specialization_constant = &self.specialization_constants;
@@ -4,7 +4,7 @@ use spirv::{Decoration, Dim, Op, StorageClass};
 use super::{FromInstruction, TypeSyntax};
 
 // From Table 3 https://docs.vulkan.org/spec/latest/chapters/interfaces.html#interfaces-resources-descset
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum DescriptorType {
     Sampler,
     SampledImage,
@@ -14,6 +14,8 @@ pub enum DescriptorType {
     StorageTexelBuffer,
     UniformBuffer,
     StorageBuffer,
+    UniformBufferDynamic,
+    StorageBufferDynamic,
     InputAttachment,
     InlineUniformBlock,
     AccelerationStructure,
@@ -51,7 +53,7 @@ impl FromInstruction for DescriptorType {
         let result_type_id = result_type.result_id?;
 
         match result_type.class.opcode {
-            Op::TypeSampler => Some(Self::Sampler), // May also be Combined Image Sampler
+            Op::TypeSampler => Some(Self::Sampler), // Combined with an image sharing its binding
 
             Op::TypeImage => {
                 let Some(Operand::Dim(dim)) = result_type.operands.get(1) else {
@@ -72,7 +74,7 @@ impl FromInstruction for DescriptorType {
                     Dim::DimSubpassData => Some(Self::InputAttachment),
 
                     _ => match sampled {
-                        1 => Some(Self::SampledImage), // May also be Combined image sampler
+                        1 => Some(Self::SampledImage), // Combined with a sampler sharing its binding
                         2 => Some(Self::StorageImage),
                         _ => None,
                     },
@@ -125,11 +127,29 @@ impl FromInstruction for DescriptorType {
     }
 }
 
+impl DescriptorType {
+    /// The dynamic variant of a buffer descriptor, which SPIR-V cannot express.
+    pub fn to_dynamic(self) -> Option<Self> {
+        match self {
+            Self::UniformBuffer => Some(Self::UniformBufferDynamic),
+            Self::StorageBuffer => Some(Self::StorageBufferDynamic),
+            _ => None,
+        }
+    }
+
+    pub fn is_dynamic(&self) -> bool {
+        matches!(
+            self,
+            Self::UniformBufferDynamic | Self::StorageBufferDynamic
+        )
+    }
+}
+
 impl TypeSyntax for DescriptorType {
     fn to_type_syntax(&self) -> syn::Type {
         match self {
-            Self::Sampler => syn::parse_quote!(ash::vk::DescriptorType::SAMPLER), // May be VK_DESCRIPTOR_TYPE_COMBINED_IMAGE_SAMPLER
-            Self::SampledImage => syn::parse_quote!(ash::vk::DescriptorType::SAMPLED_IMAGE), // May be VK_DESCRIPTOR_TYPE_COMBINED_IMAGE_SAMPLER
+            Self::Sampler => syn::parse_quote!(ash::vk::DescriptorType::SAMPLER),
+            Self::SampledImage => syn::parse_quote!(ash::vk::DescriptorType::SAMPLED_IMAGE),
             Self::StorageImage => syn::parse_quote!(ash::vk::DescriptorType::STORAGE_IMAGE),
             Self::CombinedImageSampler => {
                 syn::parse_quote!(ash::vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
@@ -140,8 +160,14 @@ impl TypeSyntax for DescriptorType {
             Self::StorageTexelBuffer => {
                 syn::parse_quote!(ash::vk::DescriptorType::STORAGE_TEXEL_BUFFER)
             }
-            Self::UniformBuffer => syn::parse_quote!(ash::vk::DescriptorType::UNIFORM_BUFFER), // May be VK_DESCRIPTOR_TYPE_UNIFORM_BUFFER_DYNAMIC
-            Self::StorageBuffer => syn::parse_quote!(ash::vk::DescriptorType::STORAGE_BUFFER), // May be VK_DESCRIPTOR_TYPE_STORAGE_BUFFER_DYNAMIC
+            Self::UniformBuffer => syn::parse_quote!(ash::vk::DescriptorType::UNIFORM_BUFFER),
+            Self::StorageBuffer => syn::parse_quote!(ash::vk::DescriptorType::STORAGE_BUFFER),
+            Self::UniformBufferDynamic => {
+                syn::parse_quote!(ash::vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC)
+            }
+            Self::StorageBufferDynamic => {
+                syn::parse_quote!(ash::vk::DescriptorType::STORAGE_BUFFER_DYNAMIC)
+            }
             Self::InputAttachment => syn::parse_quote!(ash::vk::DescriptorType::INPUT_ATTACHMENT),
             Self::InlineUniformBlock => {
                 syn::parse_quote!(ash::vk::DescriptorType::INLINE_UNIFORM_BLOCK_EXT)
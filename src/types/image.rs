@@ -0,0 +1,147 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use rspirv::dr::{Instruction, Module, Operand};
+use spirv::{Dim, ImageFormat, Op};
+
+use crate::utilities::find_instruction_with_id;
+
+use super::{FromInstruction, VulkanFormatTokens};
+
+/// A parsed `OpTypeImage`.
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub dim: Dim,
+    pub depth: bool,
+    pub arrayed: bool,
+    pub multisampled: bool,
+    pub format: ImageFormat,
+}
+
+impl Image {
+    /// The `ImageViewType` of the views bound to this image, `None` for texel buffers.
+    pub fn view_type_tokens(&self) -> Option<TokenStream> {
+        let view_type = match (self.dim, self.arrayed) {
+            (Dim::Dim1D, false) => quote! {ash::vk::ImageViewType::TYPE_1D},
+            (Dim::Dim1D, true) => quote! {ash::vk::ImageViewType::TYPE_1D_ARRAY},
+            (Dim::Dim2D | Dim::DimRect | Dim::DimSubpassData, false) => {
+                quote! {ash::vk::ImageViewType::TYPE_2D}
+            }
+            (Dim::Dim2D | Dim::DimRect | Dim::DimSubpassData, true) => {
+                quote! {ash::vk::ImageViewType::TYPE_2D_ARRAY}
+            }
+            (Dim::Dim3D, _) => quote! {ash::vk::ImageViewType::TYPE_3D},
+            (Dim::DimCube, false) => quote! {ash::vk::ImageViewType::CUBE},
+            (Dim::DimCube, true) => quote! {ash::vk::ImageViewType::CUBE_ARRAY},
+            _ => return None,
+        };
+
+        Some(view_type)
+    }
+}
+
+impl FromInstruction for Image {
+    fn from_instruction(instruction: &Instruction, spirv: &Module) -> Option<Self> {
+        match instruction.class.opcode {
+            // OpTypeImage | Result <id> | Sampled Type: <id> | Dim | Depth | Arrayed | MS | Sampled | Image Format
+            Op::TypeImage => {
+                let Some(Operand::Dim(dim)) = instruction.operands.get(1) else {
+                    return None;
+                };
+                let Some(Operand::LiteralBit32(depth)) = instruction.operands.get(2) else {
+                    return None;
+                };
+                let Some(Operand::LiteralBit32(arrayed)) = instruction.operands.get(3) else {
+                    return None;
+                };
+                let Some(Operand::LiteralBit32(multisampled)) = instruction.operands.get(4) else {
+                    return None;
+                };
+                let Some(Operand::ImageFormat(format)) = instruction.operands.get(6) else {
+                    return None;
+                };
+
+                Some(Self {
+                    dim: *dim,
+                    // A depth of 2 means the shader does not say.
+                    depth: *depth == 1,
+                    arrayed: *arrayed == 1,
+                    multisampled: *multisampled == 1,
+                    format: *format,
+                })
+            }
+
+            // OpTypeSampledImage | Result <id> | Image Type: <id>
+            Op::TypeSampledImage => {
+                let image_type =
+                    find_instruction_with_id(instruction.operands[0].unwrap_id_ref(), spirv)?;
+                Self::from_instruction(image_type, spirv)
+            }
+
+            // OpTypePointer | Result <id> | Storage Class | Type: <id>
+            Op::TypePointer => {
+                let Some(Operand::IdRef(pointer_type_id)) = instruction.operands.get(1) else {
+                    return None;
+                };
+                Self::from_instruction(find_instruction_with_id(*pointer_type_id, spirv)?, spirv)
+            }
+
+            Op::Variable => Self::from_instruction(
+                find_instruction_with_id(instruction.result_type?, spirv)?,
+                spirv,
+            ),
+
+            _ => None,
+        }
+    }
+}
+
+impl VulkanFormatTokens for Image {
+    fn to_format_tokens(&self) -> TokenStream {
+        match self.format {
+            ImageFormat::Unknown => syn::parse_quote! {ash::vk::Format::UNDEFINED},
+            ImageFormat::Rgba32f => syn::parse_quote! {ash::vk::Format::R32G32B32A32_SFLOAT},
+            ImageFormat::Rgba16f => syn::parse_quote! {ash::vk::Format::R16G16B16A16_SFLOAT},
+            ImageFormat::R32f => syn::parse_quote! {ash::vk::Format::R32_SFLOAT},
+            ImageFormat::Rgba8 => syn::parse_quote! {ash::vk::Format::R8G8B8A8_UNORM},
+            ImageFormat::Rgba8Snorm => syn::parse_quote! {ash::vk::Format::R8G8B8A8_SNORM},
+            ImageFormat::Rg32f => syn::parse_quote! {ash::vk::Format::R32G32_SFLOAT},
+            ImageFormat::Rg16f => syn::parse_quote! {ash::vk::Format::R16G16_SFLOAT},
+            ImageFormat::R11fG11fB10f => {
+                syn::parse_quote! {ash::vk::Format::B10G11R11_UFLOAT_PACK32}
+            }
+            ImageFormat::R16f => syn::parse_quote! {ash::vk::Format::R16_SFLOAT},
+            ImageFormat::Rgba16 => syn::parse_quote! {ash::vk::Format::R16G16B16A16_UNORM},
+            ImageFormat::Rgb10A2 => syn::parse_quote! {ash::vk::Format::A2B10G10R10_UNORM_PACK32},
+            ImageFormat::Rg16 => syn::parse_quote! {ash::vk::Format::R16G16_UNORM},
+            ImageFormat::Rg8 => syn::parse_quote! {ash::vk::Format::R8G8_UNORM},
+            ImageFormat::R16 => syn::parse_quote! {ash::vk::Format::R16_UNORM},
+            ImageFormat::R8 => syn::parse_quote! {ash::vk::Format::R8_UNORM},
+            ImageFormat::Rgba16Snorm => syn::parse_quote! {ash::vk::Format::R16G16B16A16_SNORM},
+            ImageFormat::Rg16Snorm => syn::parse_quote! {ash::vk::Format::R16G16_SNORM},
+            ImageFormat::Rg8Snorm => syn::parse_quote! {ash::vk::Format::R8G8_SNORM},
+            ImageFormat::R16Snorm => syn::parse_quote! {ash::vk::Format::R16_SNORM},
+            ImageFormat::R8Snorm => syn::parse_quote! {ash::vk::Format::R8_SNORM},
+            ImageFormat::Rgba32i => syn::parse_quote! {ash::vk::Format::R32G32B32A32_SINT},
+            ImageFormat::Rgba16i => syn::parse_quote! {ash::vk::Format::R16G16B16A16_SINT},
+            ImageFormat::Rgba8i => syn::parse_quote! {ash::vk::Format::R8G8B8A8_SINT},
+            ImageFormat::R32i => syn::parse_quote! {ash::vk::Format::R32_SINT},
+            ImageFormat::Rg32i => syn::parse_quote! {ash::vk::Format::R32G32_SINT},
+            ImageFormat::Rg16i => syn::parse_quote! {ash::vk::Format::R16G16_SINT},
+            ImageFormat::Rg8i => syn::parse_quote! {ash::vk::Format::R8G8_SINT},
+            ImageFormat::R16i => syn::parse_quote! {ash::vk::Format::R16_SINT},
+            ImageFormat::R8i => syn::parse_quote! {ash::vk::Format::R8_SINT},
+            ImageFormat::Rgba32ui => syn::parse_quote! {ash::vk::Format::R32G32B32A32_UINT},
+            ImageFormat::Rgba16ui => syn::parse_quote! {ash::vk::Format::R16G16B16A16_UINT},
+            ImageFormat::Rgba8ui => syn::parse_quote! {ash::vk::Format::R8G8B8A8_UINT},
+            ImageFormat::R32ui => syn::parse_quote! {ash::vk::Format::R32_UINT},
+            ImageFormat::Rgb10a2ui => syn::parse_quote! {ash::vk::Format::A2B10G10R10_UINT_PACK32},
+            ImageFormat::Rg32ui => syn::parse_quote! {ash::vk::Format::R32G32_UINT},
+            ImageFormat::Rg16ui => syn::parse_quote! {ash::vk::Format::R16G16_UINT},
+            ImageFormat::Rg8ui => syn::parse_quote! {ash::vk::Format::R8G8_UINT},
+            ImageFormat::R16ui => syn::parse_quote! {ash::vk::Format::R16_UINT},
+            ImageFormat::R8ui => syn::parse_quote! {ash::vk::Format::R8_UINT},
+            ImageFormat::R64ui => syn::parse_quote! {ash::vk::Format::R64_UINT},
+            ImageFormat::R64i => syn::parse_quote! {ash::vk::Format::R64_SINT},
+        }
+    }
+}
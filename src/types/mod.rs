@@ -2,6 +2,7 @@ use core::alloc::Layout;
 
 pub use array::*;
 pub use descriptor_types::*;
+pub use image::*;
 pub use scalar::*;
 use spirv::Op;
 pub use structure::*;
@@ -12,6 +13,7 @@ use rspirv::dr::{Instruction, Module, Operand};
 
 mod array;
 mod descriptor_types;
+mod image;
 mod scalar;
 mod structure;
 mod vector;
@@ -1,4 +1,5 @@
 use proc_macro2::TokenStream;
+use quote::quote;
 use rspirv::dr::{Instruction, Module, Operand};
 use spirv::Op;
 
@@ -120,6 +121,23 @@ impl VulkanFormatTokens for Scalar {
     }
 }
 
+impl Scalar {
+    pub fn to_component_type_tokens(&self) -> TokenStream {
+        match self {
+            Self::U8 => quote! {ash::vk::ComponentTypeKHR::UINT8},
+            Self::U16 => quote! {ash::vk::ComponentTypeKHR::UINT16},
+            Self::U32 => quote! {ash::vk::ComponentTypeKHR::UINT32},
+            Self::U64 => quote! {ash::vk::ComponentTypeKHR::UINT64},
+            Self::I8 => quote! {ash::vk::ComponentTypeKHR::SINT8},
+            Self::I16 => quote! {ash::vk::ComponentTypeKHR::SINT16},
+            Self::I32 => quote! {ash::vk::ComponentTypeKHR::SINT32},
+            Self::I64 => quote! {ash::vk::ComponentTypeKHR::SINT64},
+            Self::F32 => quote! {ash::vk::ComponentTypeKHR::FLOAT32},
+            Self::F64 => quote! {ash::vk::ComponentTypeKHR::FLOAT64},
+        }
+    }
+}
+
 impl TypeSyntax for Scalar {
     fn to_type_syntax(&self) -> syn::Type {
         match self {
@@ -5,9 +5,9 @@ use member::Member;
 use quote::{ToTokens, format_ident, quote};
 
 use rspirv::dr::{Instruction, Module, Operand};
-use spirv::Op;
+use spirv::{Decoration, Op};
 
-use crate::utilities::find_name_for_id;
+use crate::utilities::{find_instruction_with_id, find_member_name, find_name_for_id};
 
 use super::{FromInstruction, SizedType, Type, TypeSyntax};
 
@@ -73,6 +73,39 @@ impl Structure {
             layout,
         }
     }
+
+    /// Parses an `OpTypeStruct` from a storage class without explicit layout, such as
+    /// `TaskPayloadWorkgroupEXT`, laying out its members with their natural alignment.
+    pub fn from_natural_layout(instruction: &Instruction, spirv: &Module) -> Option<Self> {
+        if !matches!(instruction.class.opcode, Op::TypeStruct) {
+            return None;
+        }
+
+        let struct_id = instruction.result_id?;
+
+        if has_explicit_layout(struct_id, spirv) {
+            return Self::from_instruction(instruction, spirv);
+        }
+
+        let fields = instruction
+            .operands
+            .iter()
+            .enumerate()
+            .map(|(index, operand)| {
+                let Operand::IdRef(id) = operand else {
+                    return None;
+                };
+                let member_type =
+                    Type::from_instruction(find_instruction_with_id(*id, spirv)?, spirv)?;
+                let name = find_member_name(struct_id, index as u32, spirv)
+                    .unwrap_or_else(|| format!("field_{}", index));
+
+                Some((member_type, name))
+            })
+            .collect::<Option<_>>()?;
+
+        Some(Self::from_fields(fields, structure_name(struct_id, spirv)))
+    }
 }
 
 impl FromInstruction for Structure {
@@ -82,19 +115,7 @@ impl FromInstruction for Structure {
         }
 
         let struct_id = instruction.result_id?;
-
-        let name = match find_name_for_id(struct_id, spirv) {
-            Some(name) => {
-                if let Some(index) = name.rfind("_std430") {
-                    name[0..index].to_owned()
-                } else if let Some(index) = name.rfind("_natural") {
-                    name[0..index].to_owned()
-                } else {
-                    name.to_owned()
-                }
-            }
-            None => format!("Structure{struct_id}"),
-        };
+        let name = structure_name(struct_id, spirv);
 
         let (layout, members) = {
             let mut layout = Layout::from_size_align(0, 1).unwrap();
@@ -150,6 +171,37 @@ impl FromInstruction for Structure {
     }
 }
 
+fn structure_name(struct_id: u32, spirv: &Module) -> String {
+    match find_name_for_id(struct_id, spirv) {
+        Some(name) => {
+            if let Some(index) = name.rfind("_std430") {
+                name[0..index].to_owned()
+            } else if let Some(index) = name.rfind("_natural") {
+                name[0..index].to_owned()
+            } else {
+                name.to_owned()
+            }
+        }
+        None => format!("Structure{struct_id}"),
+    }
+}
+
+fn has_explicit_layout(struct_id: u32, spirv: &Module) -> bool {
+    spirv.annotations.iter().any(|annotation| {
+        // OpMemberDecorate | Structure Type: <id> | Member: Literal | Decoration | Literal...
+
+        if !matches!(annotation.class.opcode, Op::MemberDecorate) {
+            return false;
+        }
+
+        if annotation.operands[0].unwrap_id_ref() != struct_id {
+            return false;
+        }
+
+        annotation.operands[2].unwrap_decoration() == Decoration::Offset
+    })
+}
+
 impl SizedType for Structure {
     fn size(&self) -> usize {
         self.layout.size()
@@ -183,3 +235,51 @@ impl ToTokens for Structure {
         tokens.extend(new_tokens);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rspirv::dr::{Builder, Operand};
+    use spirv::Decoration;
+
+    use crate::{
+        types::{FromInstruction, SizedType},
+        utilities::{find_instruction_with_id, load_built_module},
+    };
+
+    use super::Structure;
+
+    #[test]
+    fn natural_layout() {
+        let mut builder = Builder::new();
+        let float = builder.type_float(32);
+        let vec3 = builder.type_vector(float, 3);
+        let payload = builder.type_struct([float, vec3]);
+        let spirv = load_built_module(builder);
+
+        let instruction = find_instruction_with_id(payload, &spirv).unwrap();
+
+        // Structures without offsets only have a layout where one is expected.
+        assert!(Structure::from_instruction(instruction, &spirv).is_none());
+
+        let structure = Structure::from_natural_layout(instruction, &spirv).unwrap();
+        assert_eq!(structure.size(), 16);
+        assert_eq!(structure.members[1].offset, 4);
+    }
+
+    #[test]
+    fn natural_layout_keeps_explicit_offsets() {
+        let mut builder = Builder::new();
+        let float = builder.type_float(32);
+        let vec4 = builder.type_vector(float, 4);
+        let block = builder.type_struct([float, vec4]);
+        builder.member_decorate(block, 0, Decoration::Offset, [Operand::LiteralBit32(0)]);
+        builder.member_decorate(block, 1, Decoration::Offset, [Operand::LiteralBit32(16)]);
+        let spirv = load_built_module(builder);
+
+        let instruction = find_instruction_with_id(block, &spirv).unwrap();
+        let structure = Structure::from_natural_layout(instruction, &spirv).unwrap();
+
+        assert_eq!(structure.size(), 32);
+        assert_eq!(structure.members[2].offset, 16);
+    }
+}
@@ -0,0 +1,27 @@
+use rspirv::dr::{Module, Operand};
+use spirv::{Decoration, Op};
+
+/// Finds the operands following the decoration of an `OpDecorate` for an id.
+pub fn find_decoration(id: u32, decoration: Decoration, spirv: &Module) -> Option<&[Operand]> {
+    spirv.annotations.iter().find_map(|annotation| {
+        // OpDecorate | Target: <id> | Decoration | Literal...
+
+        if !matches!(annotation.class.opcode, Op::Decorate) {
+            return None;
+        }
+
+        if annotation.operands[0].unwrap_id_ref() != id {
+            return None;
+        }
+
+        if annotation.operands[1].unwrap_decoration() != decoration {
+            return None;
+        }
+
+        Some(&annotation.operands[2..])
+    })
+}
+
+pub fn has_decoration(id: u32, decoration: Decoration, spirv: &Module) -> bool {
+    find_decoration(id, decoration, spirv).is_some()
+}
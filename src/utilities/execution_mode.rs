@@ -0,0 +1,35 @@
+use rspirv::dr::{Module, Operand};
+use spirv::{ExecutionMode, Op};
+
+/// Finds the operands following the mode of an `OpExecutionMode` for an entry point.
+pub fn find_execution_mode(
+    entry_point_id: u32,
+    execution_mode: ExecutionMode,
+    spirv: &Module,
+) -> Option<&[Operand]> {
+    spirv.execution_modes.iter().find_map(|instruction| {
+        // OpExecutionMode | Entry Point: <id> | Mode: Execution Mode | Literal...
+
+        if !matches!(instruction.class.opcode, Op::ExecutionMode) {
+            return None;
+        }
+
+        if instruction.operands[0].unwrap_id_ref() != entry_point_id {
+            return None;
+        }
+
+        if instruction.operands[1].unwrap_execution_mode() != execution_mode {
+            return None;
+        }
+
+        Some(&instruction.operands[2..])
+    })
+}
+
+pub fn has_execution_mode(
+    entry_point_id: u32,
+    execution_mode: ExecutionMode,
+    spirv: &Module,
+) -> bool {
+    find_execution_mode(entry_point_id, execution_mode, spirv).is_some()
+}
@@ -2,7 +2,7 @@ use itertools::Itertools;
 use proc_macro2::TokenStream;
 use quote::quote;
 use rspirv::dr::Module;
-use spirv::ExecutionModel;
+use spirv::{ExecutionModel, MemoryModel};
 
 pub fn variable_execution_models(variable_id: u32, spirv: &Module) -> Vec<ExecutionModel> {
     // OpEntryPoint | Execution Model | Entry Point: <id> | Name: Literal | <id>...
@@ -26,8 +26,9 @@ pub fn variable_execution_models(variable_id: u32, spirv: &Module) -> Vec<Execut
         .collect()
 }
 
-pub fn execution_model_to_tokens(execution_model: &ExecutionModel) -> TokenStream {
-    match execution_model {
+/// The shader stage of an execution model, `None` for the OpenCL `Kernel` model.
+pub fn execution_model_to_tokens(execution_model: &ExecutionModel) -> Option<TokenStream> {
+    let tokens = match execution_model {
         ExecutionModel::Vertex => quote! {ash::vk::ShaderStageFlags::VERTEX},
         ExecutionModel::TessellationControl => {
             quote! {ash::vk::ShaderStageFlags::TESSELLATION_CONTROL}
@@ -38,19 +39,33 @@ pub fn execution_model_to_tokens(execution_model: &ExecutionModel) -> TokenStrea
         ExecutionModel::Geometry => quote! {ash::vk::ShaderStageFlags::GEOMETRY},
         ExecutionModel::Fragment => quote! {ash::vk::ShaderStageFlags::FRAGMENT},
         ExecutionModel::GLCompute => quote! {ash::vk::ShaderStageFlags::COMPUTE},
-        ExecutionModel::Kernel => {
-            unimplemented!("ExecutionModel::Kernel has no matching ash::vk::ShaderStageFlags")
-        }
+        ExecutionModel::Kernel => return None,
         ExecutionModel::TaskNV => quote! {ash::vk::ShaderStageFlags::TASK_NV},
         ExecutionModel::MeshNV => quote! {ash::vk::ShaderStageFlags::MESH_NV},
-        ExecutionModel::RayGenerationNV => quote! {ash::vk::ShaderStageFlags::RAYGEN_NV},
-        ExecutionModel::IntersectionNV => quote! {ash::vk::ShaderStageFlags::INTERSECTION_NV},
-        ExecutionModel::AnyHitNV => quote! {ash::vk::ShaderStageFlags::ANY_HIT_NV},
-        ExecutionModel::ClosestHitNV => quote! {ash::vk::ShaderStageFlags::CLOSEST_HIT_NV},
-        ExecutionModel::MissNV => quote! {ash::vk::ShaderStageFlags::MISS_NV},
-        ExecutionModel::CallableNV => quote! {ash::vk::ShaderStageFlags::CALLABLE_NV},
+        ExecutionModel::RayGenerationNV => quote! {ash::vk::ShaderStageFlags::RAYGEN_KHR},
+        ExecutionModel::IntersectionNV => quote! {ash::vk::ShaderStageFlags::INTERSECTION_KHR},
+        ExecutionModel::AnyHitNV => quote! {ash::vk::ShaderStageFlags::ANY_HIT_KHR},
+        ExecutionModel::ClosestHitNV => quote! {ash::vk::ShaderStageFlags::CLOSEST_HIT_KHR},
+        ExecutionModel::MissNV => quote! {ash::vk::ShaderStageFlags::MISS_KHR},
+        ExecutionModel::CallableNV => quote! {ash::vk::ShaderStageFlags::CALLABLE_KHR},
         ExecutionModel::TaskEXT => quote! {ash::vk::ShaderStageFlags::TASK_EXT},
         ExecutionModel::MeshEXT => quote! {ash::vk::ShaderStageFlags::MESH_EXT},
+    };
+
+    Some(tokens)
+}
+
+/// The combined shader stages of several execution models, usable in a const context.
+pub fn execution_models_to_tokens(execution_models: &[ExecutionModel]) -> TokenStream {
+    let stages: Vec<_> = execution_models
+        .iter()
+        .filter_map(execution_model_to_tokens)
+        .collect();
+
+    match stages.as_slice() {
+        [] => quote! {ash::vk::ShaderStageFlags::empty()},
+        [stage] => stage.clone(),
+        stages => quote! {ash::vk::ShaderStageFlags::from_raw(#( #stages.as_raw() )|*)},
     }
 }
 
@@ -75,3 +90,12 @@ pub fn execution_model_to_string(execution_model: &ExecutionModel) -> &'static s
         ExecutionModel::MeshEXT => "mesh_ext",
     }
 }
+
+/// Whether the module targets OpenCL rather than Vulkan.
+pub fn is_kernel_module(spirv: &Module) -> bool {
+    // OpMemoryModel | Addressing Model | Memory Model
+
+    spirv.memory_model.as_ref().is_some_and(|instruction| {
+        instruction.operands[1].unwrap_memory_model() == MemoryModel::OpenCL
+    })
+}
@@ -0,0 +1,65 @@
+use rspirv::{
+    binary::{Consumer, ParseAction},
+    dr::{Instruction, Loader, Module, ModuleHeader},
+};
+use spirv::Op;
+
+/// Wraps [`Loader`] to also accept `OpExecutionModeId`, which the loader does not handle.
+#[derive(Default)]
+pub struct ModuleLoader {
+    loader: Loader,
+    execution_mode_ids: Vec<Instruction>,
+}
+
+impl ModuleLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn module(self) -> Module {
+        let mut module = self.loader.module();
+        module.execution_modes.extend(self.execution_mode_ids);
+        module
+    }
+}
+
+impl Consumer for ModuleLoader {
+    fn initialize(&mut self) -> ParseAction {
+        self.loader.initialize()
+    }
+
+    fn finalize(&mut self) -> ParseAction {
+        self.loader.finalize()
+    }
+
+    fn consume_header(&mut self, header: ModuleHeader) -> ParseAction {
+        self.loader.consume_header(header)
+    }
+
+    fn consume_instruction(&mut self, instruction: Instruction) -> ParseAction {
+        if matches!(instruction.class.opcode, Op::ExecutionModeId) {
+            self.execution_mode_ids.push(instruction);
+            return ParseAction::Continue;
+        }
+
+        self.loader.consume_instruction(instruction)
+    }
+}
+
+/// Assembles a module built in a test and loads it back, so its operands are typed as they are
+/// when parsed from a file.
+#[cfg(test)]
+pub fn load_built_module(builder: rspirv::dr::Builder) -> Module {
+    use rspirv::binary::{Assemble, Parser};
+
+    let bytes: Vec<u8> = builder
+        .module()
+        .assemble()
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect();
+
+    let mut loader = ModuleLoader::new();
+    Parser::new(&bytes, &mut loader).parse().unwrap();
+    loader.module()
+}
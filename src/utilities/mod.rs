@@ -1,7 +1,17 @@
 pub use debug::*;
+pub use decoration::*;
+pub use execution_mode::*;
 pub use execution_model::*;
 pub use id::*;
+pub use loader::*;
+#[cfg(test)]
+pub use syntax::*;
 
 mod debug;
+mod decoration;
+mod execution_mode;
 mod execution_model;
 mod id;
+mod loader;
+#[cfg(test)]
+mod syntax;
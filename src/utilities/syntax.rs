@@ -0,0 +1,258 @@
+use proc_macro2::TokenStream;
+use syn::{Expr, ImplItem, Item, ItemFn, ItemStruct, Pat, Stmt, visit::Visit};
+
+/// Parses generated tokens as the items of a file.
+pub fn parse_items(tokens: TokenStream) -> Vec<Item> {
+    syn::parse2::<syn::File>(tokens).unwrap().items
+}
+
+/// Finds a function by its path, such as `module::function` or `Type::method`.
+pub fn find_fn(items: &[Item], path: &str) -> Option<ItemFn> {
+    let (items, owner, name) = resolve(items, path)?;
+
+    match owner {
+        None => items.iter().find_map(|item| match item {
+            Item::Fn(function) if function.sig.ident == name => Some(function.clone()),
+            _ => None,
+        }),
+        Some(owner) => impl_items(items, owner).find_map(|item| match item {
+            ImplItem::Fn(function) if function.sig.ident == name => Some(ItemFn {
+                attrs: function.attrs.clone(),
+                vis: function.vis.clone(),
+                sig: function.sig.clone(),
+                block: Box::new(function.block.clone()),
+            }),
+            _ => None,
+        }),
+    }
+}
+
+/// Finds the value of a constant by its path, such as `module::CONSTANT` or `Type::CONSTANT`.
+pub fn find_const(items: &[Item], path: &str) -> Option<Expr> {
+    let (items, owner, name) = resolve(items, path)?;
+
+    match owner {
+        None => items.iter().find_map(|item| match item {
+            Item::Const(constant) if constant.ident == name => Some(*constant.expr.clone()),
+            _ => None,
+        }),
+        Some(owner) => impl_items(items, owner).find_map(|item| match item {
+            ImplItem::Const(constant) if constant.ident == name => Some(constant.expr.clone()),
+            _ => None,
+        }),
+    }
+}
+
+/// Finds a structure by its path, such as `module::Structure`.
+pub fn find_struct(items: &[Item], path: &str) -> Option<ItemStruct> {
+    let (items, _, name) = resolve(items, path)?;
+
+    items.iter().find_map(|item| match item {
+        Item::Struct(structure) if structure.ident == name => Some(structure.clone()),
+        _ => None,
+    })
+}
+
+/// The names and types of a structure's fields.
+pub fn struct_fields(structure: &ItemStruct) -> Vec<(String, syn::Type)> {
+    structure
+        .fields
+        .iter()
+        .map(|field| {
+            let name = field
+                .ident
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            (name, field.ty.clone())
+        })
+        .collect()
+}
+
+/// The names of a function's parameters, `self` included.
+pub fn parameter_names(function: &ItemFn) -> Vec<String> {
+    function
+        .sig
+        .inputs
+        .iter()
+        .map(|input| match input {
+            syn::FnArg::Receiver(_) => "self".to_string(),
+            syn::FnArg::Typed(typed) => match &*typed.pat {
+                Pat::Ident(ident) => ident.ident.to_string(),
+                _ => String::new(),
+            },
+        })
+        .collect()
+}
+
+/// The names of the functions and methods called by a function, in evaluation order.
+pub fn called_names(function: &ItemFn) -> Vec<String> {
+    let mut visitor = Names::default();
+    visitor.visit_item_fn(function);
+    visitor.calls
+}
+
+/// The last segment of each path referenced by a function, such as the constants it reads.
+pub fn referenced_names(function: &ItemFn) -> Vec<String> {
+    let mut visitor = Names::default();
+    visitor.visit_item_fn(function);
+    visitor.paths
+}
+
+/// The names of the fields accessed by a function, in evaluation order.
+pub fn accessed_fields(function: &ItemFn) -> Vec<String> {
+    let mut visitor = Names::default();
+    visitor.visit_item_fn(function);
+    visitor.fields
+}
+
+/// The arguments of every call to a method in a function, in evaluation order.
+pub fn method_arguments(function: &ItemFn, method: &str) -> Vec<Vec<Expr>> {
+    let mut visitor = Names::default();
+    visitor.visit_item_fn(function);
+
+    visitor
+        .method_calls
+        .into_iter()
+        .filter(|call| call.method == method)
+        .map(|call| call.args.into_iter().collect())
+        .collect()
+}
+
+/// The index of the first statement of a function's body calling a function or method.
+pub fn statement_calling(function: &ItemFn, name: &str) -> Option<usize> {
+    function.block.stmts.iter().position(|stmt: &Stmt| {
+        let mut visitor = Names::default();
+        visitor.visit_stmt(stmt);
+        visitor.calls.iter().any(|call| call == name)
+    })
+}
+
+/// The arms of the first `match` in a function, as the last segment of each path matched by the
+/// arm's pattern along with the arm's body.
+pub fn match_arms(function: &ItemFn) -> Vec<(Vec<String>, Expr)> {
+    let mut visitor = Names::default();
+    visitor.visit_item_fn(function);
+
+    let Some(arms) = visitor.match_arms else {
+        return Vec::new();
+    };
+
+    arms.into_iter()
+        .map(|arm| (pattern_names(&arm.pat), *arm.body))
+        .collect()
+}
+
+fn pattern_names(pattern: &Pat) -> Vec<String> {
+    match pattern {
+        Pat::Or(or) => or.cases.iter().flat_map(pattern_names).collect(),
+        Pat::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| vec![segment.ident.to_string()])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Descends into the modules of a path, returning the items of the innermost module, the type
+/// the item belongs to if any and the item's name.
+fn resolve<'a, 'p>(
+    mut items: &'a [Item],
+    path: &'p str,
+) -> Option<(&'a [Item], Option<&'p str>, &'p str)> {
+    let mut segments: Vec<_> = path.split("::").collect();
+    let name = segments.pop()?;
+    let mut owner = None;
+
+    for (index, segment) in segments.iter().enumerate() {
+        let module = items.iter().find_map(|item| match item {
+            Item::Mod(module) if module.ident == segment => module.content.as_ref(),
+            _ => None,
+        });
+
+        match module {
+            Some((_, content)) => items = content,
+            None if index == segments.len() - 1 => owner = Some(*segment),
+            None => return None,
+        }
+    }
+
+    Some((items, owner, name))
+}
+
+fn impl_items<'a>(items: &'a [Item], owner: &'a str) -> impl Iterator<Item = &'a ImplItem> {
+    items
+        .iter()
+        .filter_map(move |item| match item {
+            Item::Impl(block) => match &*block.self_ty {
+                syn::Type::Path(path) if path.path.is_ident(owner) => Some(&block.items),
+                _ => None,
+            },
+            _ => None,
+        })
+        .flatten()
+}
+
+#[derive(Default)]
+struct Names {
+    calls: Vec<String>,
+    paths: Vec<String>,
+    fields: Vec<String>,
+    method_calls: Vec<syn::ExprMethodCall>,
+    match_arms: Option<Vec<syn::Arm>>,
+}
+
+impl<'ast> Visit<'ast> for Names {
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        syn::visit::visit_expr_call(self, call);
+
+        if let Expr::Path(path) = &*call.func {
+            if let Some(segment) = path.path.segments.last() {
+                self.calls.push(segment.ident.to_string());
+            }
+        }
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        syn::visit::visit_expr_method_call(self, call);
+        self.calls.push(call.method.to_string());
+        self.method_calls.push(call.clone());
+    }
+
+    fn visit_expr_match(&mut self, expression: &'ast syn::ExprMatch) {
+        if self.match_arms.is_none() {
+            self.match_arms = Some(expression.arms.clone());
+        }
+
+        syn::visit::visit_expr_match(self, expression);
+    }
+
+    // Macros such as `vec!` whose body is a list of expressions are visited as such.
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Ok(expressions) = mac
+            .parse_body_with(syn::punctuated::Punctuated::<Expr, syn::Token![,]>::parse_terminated)
+        {
+            expressions
+                .iter()
+                .for_each(|expression| self.visit_expr(expression));
+        }
+    }
+
+    fn visit_expr_field(&mut self, field: &'ast syn::ExprField) {
+        syn::visit::visit_expr_field(self, field);
+
+        if let syn::Member::Named(ident) = &field.member {
+            self.fields.push(ident.to_string());
+        }
+    }
+
+    fn visit_expr_path(&mut self, path: &'ast syn::ExprPath) {
+        syn::visit::visit_expr_path(self, path);
+
+        if let Some(segment) = path.path.segments.last() {
+            self.paths.push(segment.ident.to_string());
+        }
+    }
+}